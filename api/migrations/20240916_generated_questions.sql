@@ -0,0 +1,11 @@
+CREATE TABLE generated_questions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    word_id INTEGER NOT NULL REFERENCES words(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    question TEXT NOT NULL,
+    served INTEGER NOT NULL DEFAULT 0,
+    last_served_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX generated_questions_word_kind ON generated_questions (word_id, kind);
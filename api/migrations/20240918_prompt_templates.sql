@@ -0,0 +1,20 @@
+CREATE TABLE prompt_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    system_prompt TEXT NOT NULL,
+    user_prompt TEXT NOT NULL,
+    response_schema TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (name, version)
+);
+
+INSERT INTO prompt_templates (name, version, system_prompt, user_prompt, response_schema) VALUES (
+    'multiple_choice',
+    1,
+    'I''m building a {{ target_language }} learning app. Please suggest me a question related to the word below and three to five other single word options  which the user can choose from. One or more options should be correct. The question should be in {{ source_language }}. Don''t ask what the word means in {{ source_language }}.',
+    'The word is ''{{ word.word }}''',
+    '{"type": "object", "properties": {"question": {"type": "string"}, "options": {"type": "array", "items": {"type": "object", "properties": {"name": {"type": "string"}, "correct": {"type": "boolean"}}, "additionalProperties": false, "required": ["name", "correct"]}}}, "additionalProperties": false, "required": ["question", "options"]}'
+);
+
+ALTER TABLE generated_questions ADD COLUMN template_id INTEGER REFERENCES prompt_templates(id);
@@ -0,0 +1,5 @@
+ALTER TABLE collections ADD COLUMN source_language TEXT NOT NULL DEFAULT 'en';
+ALTER TABLE collections ADD COLUMN target_language TEXT NOT NULL DEFAULT 'no';
+
+ALTER TABLE words ADD COLUMN source_language TEXT;
+ALTER TABLE words ADD COLUMN target_language TEXT;
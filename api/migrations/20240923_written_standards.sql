@@ -0,0 +1,21 @@
+CREATE TABLE word_spellings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    word_id INTEGER NOT NULL REFERENCES words(id) ON DELETE CASCADE,
+    language TEXT NOT NULL,
+    spelling TEXT NOT NULL,
+    UNIQUE (word_id, language)
+);
+
+ALTER TABLE collections ADD COLUMN accept_other_standards BOOLEAN NOT NULL DEFAULT TRUE;
+
+ALTER TABLE generated_questions ADD COLUMN target_language TEXT NOT NULL DEFAULT 'no';
+
+INSERT INTO prompt_templates (name, version, system_prompt, user_prompt, response_schema)
+SELECT
+    name,
+    2,
+    system_prompt || ' Only use {{ target_language }} spelling, never the spelling of another written standard.',
+    'The word is ''{{ spelling }}''',
+    response_schema
+FROM prompt_templates
+WHERE name = 'multiple_choice' AND version = 1;
@@ -0,0 +1,11 @@
+CREATE TABLE reviews (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    word_id INTEGER NOT NULL REFERENCES words(id) ON DELETE CASCADE,
+    user_id INTEGER REFERENCES users(id) ON DELETE CASCADE,
+    correct BOOLEAN NOT NULL,
+    box INTEGER NOT NULL,
+    due_at TIMESTAMP NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX reviews_word ON reviews (word_id);
@@ -0,0 +1,15 @@
+CREATE TABLE tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE word_tags (
+    word_id INTEGER NOT NULL REFERENCES words(id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (word_id, tag_id)
+);
+
+ALTER TABLE words ADD COLUMN part_of_speech TEXT;
+ALTER TABLE words ADD COLUMN difficulty INTEGER;
+
+ALTER TABLE collections ADD COLUMN filter TEXT;
@@ -0,0 +1,5 @@
+ALTER TABLE collections ADD COLUMN owner_id INTEGER REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE collections ADD COLUMN visibility TEXT NOT NULL DEFAULT 'private' CHECK (visibility IN ('private', 'unlisted', 'public'));
+ALTER TABLE collections ADD COLUMN cloned_from INTEGER REFERENCES collections(id) ON DELETE SET NULL;
+
+CREATE INDEX collections_visibility ON collections (visibility);
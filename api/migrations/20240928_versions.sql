@@ -0,0 +1,28 @@
+ALTER TABLE words ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE words ADD COLUMN updated_at DATETIME;
+ALTER TABLE collections ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE collections ADD COLUMN updated_at DATETIME;
+
+-- Versions are bumped by the database so every write path counts, including
+-- ones that only touch the word list of a collection. Recursive triggers are
+-- off, so the inner UPDATE does not fire the trigger again. Changes to the
+-- word list touch the collection row, which bumps its version in turn.
+CREATE TRIGGER words_version AFTER UPDATE ON words
+BEGIN
+    UPDATE words SET version = OLD.version + 1, updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER collections_version AFTER UPDATE ON collections
+BEGIN
+    UPDATE collections SET version = OLD.version + 1, updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER collection_words_insert_version AFTER INSERT ON collection_words
+BEGIN
+    UPDATE collections SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.collection_id;
+END;
+
+CREATE TRIGGER collection_words_delete_version AFTER DELETE ON collection_words
+BEGIN
+    UPDATE collections SET updated_at = CURRENT_TIMESTAMP WHERE id = OLD.collection_id;
+END;
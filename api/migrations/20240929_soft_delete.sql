@@ -0,0 +1,7 @@
+ALTER TABLE words ADD COLUMN deleted_at DATETIME;
+ALTER TABLE collections ADD COLUMN deleted_at DATETIME;
+ALTER TABLE tags ADD COLUMN deleted_at DATETIME;
+
+CREATE INDEX words_deleted_at ON words (deleted_at);
+CREATE INDEX collections_deleted_at ON collections (deleted_at);
+CREATE INDEX tags_deleted_at ON tags (deleted_at);
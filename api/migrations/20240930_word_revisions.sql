@@ -0,0 +1,16 @@
+CREATE TABLE word_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    word_id INTEGER NOT NULL REFERENCES words(id) ON DELETE CASCADE,
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    -- {"<field>": {"from": <old value>, "to": <new value>}, ...}
+    changes TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX word_revisions_word ON word_revisions (word_id, id);
+
+-- Revisions are history, only purging a word may remove them
+CREATE TRIGGER word_revisions_append_only BEFORE UPDATE ON word_revisions
+BEGIN
+    SELECT RAISE(ABORT, 'word revisions are append-only');
+END;
@@ -0,0 +1,3 @@
+ALTER TABLE sessions ADD COLUMN expires_at DATETIME;
+
+UPDATE sessions SET expires_at = datetime(created_at, '+30 days');
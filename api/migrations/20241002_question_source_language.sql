@@ -0,0 +1,7 @@
+-- Questions are written in the source language of the collection, so pools
+-- are kept per language pair. Earlier questions were asked in English.
+ALTER TABLE generated_questions ADD COLUMN source_language TEXT NOT NULL DEFAULT 'en';
+
+DROP INDEX generated_questions_word_kind;
+
+CREATE INDEX generated_questions_pool ON generated_questions (word_id, kind, source_language, target_language);
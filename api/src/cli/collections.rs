@@ -0,0 +1,91 @@
+use serde_json::Value;
+
+use crate::controllers::{
+    collections::{Collection, CollectionsController},
+    controller::Controller,
+};
+
+use super::{columns, connect, describe, parse_id, USAGE};
+
+pub async fn run(args: &[&str]) -> Result<(), String> {
+    let pool = connect().await?;
+    let controller = CollectionsController::new(&pool);
+
+    match args {
+        ["list"] => {
+            let collections = controller.get_all().await.map_err(|err| err.to_string())?;
+
+            for collection in &collections {
+                print_collection(collection);
+            }
+
+            Ok(())
+        }
+        ["add", name, rest @ ..] => {
+            let mut item = columns(rest)?;
+            item.insert("name".to_string(), Value::from(*name));
+            item.entry("description").or_insert_with(|| Value::from(""));
+
+            let owner_id = item
+                .remove("owner_id")
+                .and_then(|owner_id| owner_id.as_i64());
+
+            let id = controller
+                .create_as(owner_id, Value::Object(item))
+                .await
+                .map_err(|err| err.to_string())?;
+
+            print_collection(
+                &controller
+                    .get_one(id)
+                    .await
+                    .map_err(describe("collection", id))?,
+            );
+
+            Ok(())
+        }
+        ["edit", id, rest @ ..] if !rest.is_empty() => {
+            let id = parse_id(id)?;
+
+            controller
+                .get_info(id)
+                .await
+                .map_err(describe("collection", id))?;
+            controller
+                .update(id, Value::Object(columns(rest)?))
+                .await
+                .map_err(|err| err.to_string())?;
+
+            print_collection(
+                &controller
+                    .get_one(id)
+                    .await
+                    .map_err(describe("collection", id))?,
+            );
+
+            Ok(())
+        }
+        ["delete", id] => {
+            let id = parse_id(id)?;
+
+            controller
+                .get_info(id)
+                .await
+                .map_err(describe("collection", id))?;
+            controller.delete(id).await.map_err(|err| err.to_string())?;
+
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn print_collection(collection: &Collection) {
+    println!(
+        "{}\t{}\t{}\t{} words",
+        collection.id,
+        collection.name,
+        collection.visibility,
+        collection.words.len()
+    );
+}
@@ -0,0 +1,158 @@
+use std::{fs, path::Path, str::FromStr};
+
+use sqlx::{sqlite::SqliteConnectOptions, SqlitePool};
+
+use crate::{
+    controllers::{
+        dump::{Dump, DumpController, ImportMode},
+        maintenance::MaintenanceController,
+        reviews::ReviewsController,
+    },
+    MIGRATOR,
+};
+
+use super::{connect, database_url};
+
+pub async fn export(file: &str) -> Result<(), String> {
+    let pool = connect().await?;
+
+    let dump = DumpController::new(&pool)
+        .export()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let json = serde_json::to_string_pretty(&dump).map_err(|err| err.to_string())?;
+
+    fs::write(file, json).map_err(|err| err.to_string())?;
+
+    println!(
+        "Exported {} users, {} words, {} collections and {} reviews to {}",
+        dump.users.len(),
+        dump.words.len(),
+        dump.collections.len(),
+        dump.reviews.len(),
+        file
+    );
+
+    Ok(())
+}
+
+pub async fn import(file: &str, mode: ImportMode) -> Result<(), String> {
+    let json = fs::read_to_string(file).map_err(|err| format!("{}: {}", file, err))?;
+    let dump: Dump = serde_json::from_str(&json).map_err(|err| format!("{}: {}", file, err))?;
+
+    let pool = connect().await?;
+
+    let summary = DumpController::new(&pool)
+        .import(&dump, mode)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    println!(
+        "Imported {} users, {} words, {} collections and {} reviews",
+        summary.users, summary.words, summary.collections, summary.reviews
+    );
+
+    Ok(())
+}
+
+pub async fn migrate() -> Result<(), String> {
+    let options = SqliteConnectOptions::from_str(&database_url()?)
+        .map_err(|err| err.to_string())?
+        .create_if_missing(true);
+
+    let pool = SqlitePool::connect_with(options)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    MIGRATOR.run(&pool).await.map_err(|err| err.to_string())?;
+
+    println!("The database is up to date");
+
+    Ok(())
+}
+
+pub async fn stats(recompute: bool) -> Result<(), String> {
+    let pool = connect().await?;
+
+    if recompute {
+        let changed = ReviewsController::new(&pool)
+            .recompute_difficulty()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        println!("Updated the difficulty of {} words", changed);
+    }
+
+    let stats = MaintenanceController::new(&pool)
+        .stats()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    println!("users\t{}", stats.users);
+    println!("words\t{}", stats.words);
+    println!("due words\t{}", stats.due_words);
+    println!("collections\t{}", stats.collections);
+    println!("tags\t{}", stats.tags);
+    println!("reviews\t{}", stats.reviews);
+    println!("generated questions\t{}", stats.generated_questions);
+    println!("in the trash\t{}", stats.trashed);
+
+    Ok(())
+}
+
+/// Path of the database file behind `DATABASE_URL`, e.g. `sqlite:data.db`.
+fn database_path() -> Result<String, String> {
+    let url = database_url()?;
+
+    let path = url
+        .strip_prefix("sqlite://")
+        .or_else(|| url.strip_prefix("sqlite:"))
+        .ok_or_else(|| format!("{} is not a SQLite database url", url))?;
+
+    Ok(path.split('?').next().unwrap_or_default().to_string())
+}
+
+pub async fn restore(backup: &str) -> Result<(), String> {
+    if !Path::new(backup).is_file() {
+        return Err(format!("{} does not exist", backup));
+    }
+
+    let pool = SqlitePool::connect(&format!("sqlite:{}?mode=ro", backup))
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let problems = MaintenanceController::new(&pool)
+        .integrity_check()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    pool.close().await;
+
+    if !problems.is_empty() {
+        return Err(format!(
+            "{} failed the integrity check:\n{}",
+            backup,
+            problems.join("\n")
+        ));
+    }
+
+    let database = database_path()?;
+
+    // Moving the WAL along keeps it from being applied to the backup, and
+    // keeps the moved database complete
+    for suffix in ["", "-wal", "-shm"] {
+        let path = format!("{}{}", database, suffix);
+
+        if Path::new(&path).exists() {
+            fs::rename(&path, format!("{}.before-restore{}", database, suffix))
+                .map_err(|err| err.to_string())?;
+        }
+    }
+
+    fs::copy(backup, &database).map_err(|err| err.to_string())?;
+
+    println!("Restored {} from {}", database, backup);
+
+    Ok(())
+}
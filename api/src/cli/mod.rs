@@ -0,0 +1,163 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, Write},
+};
+
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use utoipa::OpenApi;
+
+use crate::{codegen, controllers::dump::ImportMode, endpoints::docs::ApiDoc};
+
+mod collections;
+mod data;
+mod practice;
+mod users;
+mod words;
+
+const USAGE: &str = "Usage: drue [<command>]
+
+Without a command the server is started. Apart from codegen and openapi the
+commands work directly on the database at DATABASE_URL.
+
+Commands:
+  words list [<tag>]            Lists words, optionally only those with a tag.
+  words add <word> <translation> [<column>=<value>...]
+  words edit <id> <column>=<value>...
+                                Values are read as JSON, or as text if they
+                                aren't valid JSON.
+  words delete <id>             Moves a word to the trash.
+  collections list
+  collections add <name> [<column>=<value>...]
+  collections edit <id> <column>=<value>...
+                                words=[1,2,3] replaces the word list.
+  collections delete <id>       Moves a collection to the trash.
+  users list
+  users add <name>              Reads the password from stdin.
+  users password <name>         Sets a new password read from stdin and ends
+                                the user's sessions.
+  practice --collection <id> [--server <url>]
+                                Drills a collection in the terminal, against
+                                the database or a running server. DRUE_TOKEN
+                                logs in to the server.
+  export <file>                 Writes a JSON dump of the whole dataset.
+  import <file> [--restore]     Merges a dump into the database, or restores
+                                it into an empty one keeping its ids.
+  migrate                       Applies pending migrations, creating the
+                                database if it doesn't exist.
+  stats [--recompute]           Prints row counts. --recompute first derives
+                                word difficulty from the review history.
+  restore <backup>              Replaces the database with a backup. Stop the
+                                server first. The current database is kept
+                                next to it with a .before-restore suffix.
+  codegen [<file>]              Writes TypeScript definitions of the API
+                                types to a file, or prints them.
+  openapi                       Prints the OpenAPI document of the HTTP API.";
+
+/// Runs a command given on the command line, returning the exit code.
+pub async fn run(args: &[String]) -> i32 {
+    let result = match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["words", args @ ..] => words::run(args).await,
+        ["collections", args @ ..] => collections::run(args).await,
+        ["users", args @ ..] => users::run(args).await,
+        ["practice", args @ ..] => practice::run(args).await,
+        ["export", file] => data::export(file).await,
+        ["import", file] => data::import(file, ImportMode::Merge).await,
+        ["import", file, "--restore"] => data::import(file, ImportMode::Restore).await,
+        ["migrate"] => data::migrate().await,
+        ["stats"] => data::stats(false).await,
+        ["stats", "--recompute"] => data::stats(true).await,
+        ["restore", backup] => data::restore(backup).await,
+        ["codegen"] => codegen(None),
+        ["codegen", file] => codegen(Some(file)),
+        ["openapi"] => openapi(),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(message) => {
+            eprintln!("{}", message);
+            1
+        }
+    }
+}
+
+fn codegen(file: Option<&str>) -> Result<(), String> {
+    let definitions = codegen::typescript().map_err(|err| err.to_string())?;
+
+    match file {
+        Some(file) => fs::write(file, definitions).map_err(|err| err.to_string()),
+        None => {
+            print!("{}", definitions);
+
+            Ok(())
+        }
+    }
+}
+
+fn openapi() -> Result<(), String> {
+    let json = ApiDoc::openapi()
+        .to_pretty_json()
+        .map_err(|err| err.to_string())?;
+
+    println!("{}", json);
+
+    Ok(())
+}
+
+fn database_url() -> Result<String, String> {
+    env::var("DATABASE_URL").map_err(|_| "DATABASE_URL is not set".to_string())
+}
+
+async fn connect() -> Result<SqlitePool, String> {
+    SqlitePool::connect(&database_url()?)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+fn parse_id(id: &str) -> Result<i64, String> {
+    id.parse().map_err(|_| format!("{} is not an id", id))
+}
+
+/// Turns `column=value` arguments into the JSON object controllers take.
+fn columns(args: &[&str]) -> Result<Map<String, Value>, String> {
+    args.iter()
+        .map(|arg| {
+            let (column, value) = arg
+                .split_once('=')
+                .ok_or_else(|| format!("{} is not of the form <column>=<value>", arg))?;
+
+            let value = serde_json::from_str(value).unwrap_or_else(|_| Value::from(value));
+
+            Ok((column.to_string(), value))
+        })
+        .collect()
+}
+
+/// Explains a failed lookup by id instead of sqlx's "no rows returned".
+fn describe(kind: &str, id: i64) -> impl Fn(sqlx::Error) -> String + '_ {
+    move |err| match err {
+        sqlx::Error::RowNotFound => format!("there is no {} with id {}", kind, id),
+        err => err.to_string(),
+    }
+}
+
+/// Reads one line from stdin, so passwords can be piped in.
+fn read_line(prompt: &str) -> Result<String, String> {
+    eprint!("{}", prompt);
+    io::stderr().flush().map_err(|err| err.to_string())?;
+
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|err| err.to_string())?;
+
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
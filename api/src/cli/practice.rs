@@ -0,0 +1,248 @@
+use std::{
+    env,
+    io::{self, BufRead, Write},
+};
+
+use rand::seq::SliceRandom;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::de::DeserializeOwned;
+use sqlx::SqlitePool;
+
+use crate::{
+    controllers::{
+        collections::{Collection, CollectionsController},
+        controller::Controller,
+        words::WordsController,
+    },
+    endpoints::words::Answer,
+    languages::grading::{Direction, Grade},
+    llm::{offline, prompt::PromptContext},
+};
+
+use super::{connect, describe, parse_id, USAGE};
+
+const DISTRACTORS: usize = 3;
+
+/// Where answers are graded and reviews recorded.
+enum Backend {
+    Local(SqlitePool),
+    Remote(Remote),
+}
+
+/// A running server, authenticated with `DRUE_TOKEN` if set.
+struct Remote {
+    client: Client,
+    url: String,
+    token: Option<String>,
+}
+
+impl Backend {
+    async fn collection(&self, id: i64) -> Result<Collection, String> {
+        match self {
+            Backend::Local(pool) => CollectionsController::new(pool)
+                .get_one(id)
+                .await
+                .map_err(describe("collection", id)),
+            Backend::Remote(remote) => {
+                remote
+                    .send(remote.request(Method::GET, &format!("/api/collections/{}", id)))
+                    .await
+            }
+        }
+    }
+
+    async fn answer(&self, word_id: i64, answer: &Answer) -> Result<Grade, String> {
+        match self {
+            Backend::Local(pool) => WordsController::new(pool)
+                .answer(
+                    word_id,
+                    None,
+                    answer.collection_id,
+                    answer.direction,
+                    &answer.answer,
+                )
+                .await
+                .map_err(describe("word", word_id)),
+            Backend::Remote(remote) => {
+                remote
+                    .send(
+                        remote
+                            .request(Method::POST, &format!("/api/words/{}/answer", word_id))
+                            .json(answer),
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+impl Remote {
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.client.request(
+            method,
+            format!("{}{}", self.url.trim_end_matches('/'), path),
+        );
+
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, String> {
+        let response = request.send().await.map_err(|err| err.to_string())?;
+        let status = response.status();
+
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+
+            return Err(format!("the server responded with {}: {}", status, body));
+        }
+
+        response.json().await.map_err(|err| err.to_string())
+    }
+}
+
+/// `practice --collection <id> [--server <url>]`
+pub async fn run(args: &[&str]) -> Result<(), String> {
+    let (collection_id, server) = match args {
+        ["--collection", id] => (parse_id(id)?, None),
+        ["--collection", id, "--server", url] | ["--server", url, "--collection", id] => {
+            (parse_id(id)?, Some(url.to_string()))
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let backend = match server {
+        Some(url) => Backend::Remote(Remote {
+            client: Client::new(),
+            url,
+            token: env::var("DRUE_TOKEN").ok(),
+        }),
+        None => Backend::Local(connect().await?),
+    };
+
+    let collection = backend.collection(collection_id).await?;
+
+    if collection.words.is_empty() {
+        return Err(format!("{} has no words to practice", collection.name));
+    }
+
+    let mut words = collection.words.clone();
+    words.shuffle(&mut rand::thread_rng());
+
+    println!(
+        "Practicing {} ({} words). Answer with the number or type the word, an empty line quits.\n",
+        collection.name,
+        words.len()
+    );
+
+    let mut answered = 0;
+    let mut correct = 0;
+
+    for (i, word) in words.iter().enumerate() {
+        // Duplicate words would show up as identical choices
+        let mut candidates = collection
+            .words
+            .iter()
+            .filter(|candidate| candidate.word != word.word)
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| a.word.cmp(&b.word));
+        candidates.dedup_by(|a, b| a.word == b.word);
+
+        let distractors = candidates
+            .choose_multiple(&mut rand::thread_rng(), DISTRACTORS)
+            .map(|candidate| (*candidate).clone())
+            .collect::<Vec<_>>();
+
+        let context = PromptContext::new(word, &[], Some(&collection), "beginner");
+
+        let question = match offline::generate_question(&context, &distractors) {
+            Ok(question) => question,
+            Err(violations) => {
+                println!(
+                    "[{}/{}] Skipping {}: {}\n",
+                    i + 1,
+                    words.len(),
+                    word.word,
+                    violations
+                        .iter()
+                        .map(|violation| violation.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+                continue;
+            }
+        };
+
+        println!("[{}/{}] {}", i + 1, words.len(), question.question);
+        println!(
+            "{}",
+            if word.audio.is_empty() {
+                "    (no audio)"
+            } else {
+                "    (audio available in the app)"
+            }
+        );
+
+        for (number, option) in question.options.iter().enumerate() {
+            println!("  {}. {}", number + 1, option.name);
+        }
+
+        let input = prompt("> ")?;
+
+        if input.is_empty() {
+            break;
+        }
+
+        let answer = match input.parse::<usize>() {
+            Ok(number) if (1..=question.options.len()).contains(&number) => {
+                question.options[number - 1].name.clone()
+            }
+            _ => input,
+        };
+
+        let grade = backend
+            .answer(
+                word.id,
+                &Answer {
+                    answer,
+                    direction: Direction::ToTarget,
+                    collection_id: Some(collection.id),
+                },
+            )
+            .await?;
+
+        answered += 1;
+
+        if grade.correct {
+            correct += 1;
+            println!("Correct!");
+        } else {
+            println!("Wrong, it is {}", grade.expected);
+        }
+
+        if let Some(standard) = grade.other_standard {
+            println!("Note: that is the {} spelling", standard);
+        }
+
+        println!();
+    }
+
+    println!("{} of {} correct", correct, answered);
+
+    Ok(())
+}
+
+fn prompt(prompt: &str) -> Result<String, String> {
+    print!("{}", prompt);
+    io::stdout().flush().map_err(|err| err.to_string())?;
+
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|err| err.to_string())?;
+
+    Ok(line.trim().to_string())
+}
@@ -0,0 +1,69 @@
+use crate::controllers::{
+    controller::Controller,
+    users::{Credentials, UsersController},
+};
+
+use super::{connect, read_line, USAGE};
+
+pub async fn run(args: &[&str]) -> Result<(), String> {
+    let pool = connect().await?;
+    let controller = UsersController::new(&pool);
+
+    match args {
+        ["list"] => {
+            let users = controller.get_all().await.map_err(|err| err.to_string())?;
+
+            for user in &users {
+                println!("{}\t{}", user.id, user.name);
+            }
+
+            Ok(())
+        }
+        ["add", name] => {
+            let credentials = Credentials {
+                name: name.to_string(),
+                password: password()?,
+            };
+
+            let id = controller
+                .register(&credentials)
+                .await
+                .map_err(|err| match err {
+                    sqlx::Error::Database(err) if err.is_unique_violation() => {
+                        format!("{} is already taken", name)
+                    }
+                    err => err.to_string(),
+                })?;
+
+            println!("{}\t{}", id, name);
+
+            Ok(())
+        }
+        ["password", name] => {
+            let credentials = Credentials {
+                name: name.to_string(),
+                password: password()?,
+            };
+
+            match controller
+                .set_password(&credentials)
+                .await
+                .map_err(|err| err.to_string())?
+            {
+                0 => Err(format!("there is no user named {}", name)),
+                _ => Ok(()),
+            }
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn password() -> Result<String, String> {
+    let password = read_line("Password: ")?;
+
+    if password.is_empty() {
+        return Err("the password must not be empty".to_string());
+    }
+
+    Ok(password)
+}
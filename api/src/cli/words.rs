@@ -0,0 +1,79 @@
+use serde_json::Value;
+
+use crate::controllers::{
+    controller::Controller,
+    words::{Word, WordFilter, WordsController},
+};
+
+use super::{columns, connect, describe, parse_id, USAGE};
+
+pub async fn run(args: &[&str]) -> Result<(), String> {
+    let pool = connect().await?;
+    let controller = WordsController::new(&pool);
+
+    match args {
+        ["list"] => list(&controller, None).await,
+        ["list", tag] => list(&controller, Some(tag)).await,
+        ["add", word, translation, rest @ ..] => {
+            let mut item = columns(rest)?;
+            item.insert("word".to_string(), Value::from(*word));
+            item.insert("translation".to_string(), Value::from(*translation));
+
+            for column in ["image", "audio"] {
+                item.entry(column).or_insert_with(|| Value::from(""));
+            }
+
+            let id = controller
+                .create(Value::Object(item))
+                .await
+                .map_err(|err| err.to_string())?;
+
+            print_word(&controller.get_one(id).await.map_err(describe("word", id))?);
+
+            Ok(())
+        }
+        ["edit", id, rest @ ..] if !rest.is_empty() => {
+            let id = parse_id(id)?;
+
+            controller
+                .update_as(id, None, None, Value::Object(columns(rest)?))
+                .await
+                .map_err(describe("word", id))?;
+
+            print_word(&controller.get_one(id).await.map_err(describe("word", id))?);
+
+            Ok(())
+        }
+        ["delete", id] => {
+            let id = parse_id(id)?;
+
+            controller.get_one(id).await.map_err(describe("word", id))?;
+            controller.delete(id).await.map_err(|err| err.to_string())?;
+
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+async fn list(controller: &WordsController<'_>, tag: Option<&str>) -> Result<(), String> {
+    let filter = WordFilter {
+        tags: tag.map(str::to_string).into_iter().collect(),
+        ..Default::default()
+    };
+
+    let words = controller
+        .get_filtered(&filter)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    for word in &words {
+        print_word(word);
+    }
+
+    Ok(())
+}
+
+fn print_word(word: &Word) {
+    println!("{}\t{}\t{}", word.id, word.word, word.translation);
+}
@@ -0,0 +1,177 @@
+use serde_json::{Map, Value};
+use utoipa::OpenApi;
+
+use crate::endpoints::docs::ApiDoc;
+
+const HEADER: &str = "// Generated by `drue codegen` from the API's OpenAPI schemas. Do not edit.
+";
+
+/// TypeScript definitions for every schema in the OpenAPI document, so the
+/// frontend types come from the same source as `/api/openapi.json`.
+pub fn typescript() -> Result<String, serde_json::Error> {
+    let spec = serde_json::to_value(ApiDoc::openapi())?;
+
+    let mut out = HEADER.to_string();
+
+    if let Some(schemas) = spec["components"]["schemas"].as_object() {
+        for (name, schema) in schemas {
+            out.push('\n');
+            out.push_str(&comment(schema, ""));
+            out.push_str(&declaration(name, schema));
+        }
+    }
+
+    Ok(out)
+}
+
+fn declaration(name: &str, schema: &Value) -> String {
+    match schema.get("properties") {
+        Some(Value::Object(properties)) => format!(
+            "export interface {} {}\n",
+            name,
+            object(properties, schema, "")
+        ),
+        _ => format!("export type {} = {}\n", name, ts_type(schema, "")),
+    }
+}
+
+fn ts_type(schema: &Value, indent: &str) -> String {
+    if let Some(reference) = schema["$ref"].as_str() {
+        return reference.rsplit('/').next().unwrap_or_default().to_string();
+    }
+
+    if let Some(variants) = schema["oneOf"].as_array() {
+        return join(variants, " | ", indent);
+    }
+
+    if let Some(parts) = schema["allOf"].as_array() {
+        return join(parts, " & ", indent);
+    }
+
+    if let Some(values) = schema["enum"].as_array() {
+        return values
+            .iter()
+            .map(|value| match value.as_str() {
+                Some(text) => quote(text),
+                None => value.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+
+    match &schema["type"] {
+        // `["integer", "null"]` is how optional fields come out
+        Value::Array(types) => types
+            .iter()
+            .map(|kind| primitive(kind.as_str().unwrap_or_default(), schema, indent))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        Value::String(kind) => primitive(kind, schema, indent),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn primitive(kind: &str, schema: &Value, indent: &str) -> String {
+    match kind {
+        "string" => "string".to_string(),
+        "integer" | "number" => "number".to_string(),
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        "array" => {
+            let items = ts_type(&schema["items"], indent);
+
+            if items.contains(' ') {
+                format!("({})[]", items)
+            } else {
+                format!("{}[]", items)
+            }
+        }
+        "object" => match (&schema["properties"], &schema["additionalProperties"]) {
+            (Value::Object(properties), _) => object(properties, schema, indent),
+            (_, values @ Value::Object(_)) => {
+                format!("Record<string, {}>", ts_type(values, indent))
+            }
+            _ => "Record<string, unknown>".to_string(),
+        },
+        _ => "unknown".to_string(),
+    }
+}
+
+fn object(properties: &Map<String, Value>, schema: &Value, indent: &str) -> String {
+    let required = schema["required"]
+        .as_array()
+        .map(|names| names.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let inner = format!("{}  ", indent);
+
+    let mut out = "{\n".to_string();
+
+    for (name, property) in properties {
+        out.push_str(&comment(property, &inner));
+        out.push_str(&format!(
+            "{}{}{}: {}\n",
+            inner,
+            property_name(name),
+            if required.contains(&name.as_str()) {
+                ""
+            } else {
+                "?"
+            },
+            ts_type(property, &inner)
+        ));
+    }
+
+    out.push_str(indent);
+    out.push('}');
+
+    out
+}
+
+fn join(schemas: &[Value], separator: &str, indent: &str) -> String {
+    schemas
+        .iter()
+        .map(|schema| ts_type(schema, indent))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn property_name(name: &str) -> String {
+    let identifier = name.chars().enumerate().all(|(i, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+    });
+
+    if identifier && !name.is_empty() {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+/// A string literal the way the app's prettier config writes them
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn comment(schema: &Value, indent: &str) -> String {
+    // References carry their description on the referenced schema
+    let description = schema["description"].as_str().or_else(|| {
+        schema["oneOf"]
+            .as_array()?
+            .iter()
+            .find_map(|variant| variant["description"].as_str())
+    });
+
+    match description {
+        Some(text) => {
+            let lines = text
+                .lines()
+                .map(|line| format!("{} * {}", indent, line).trim_end().to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            format!("{}/**\n{}\n{} */\n", indent, lines, indent)
+        }
+        None => String::new(),
+    }
+}
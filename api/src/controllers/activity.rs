@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, SqlitePool};
+use utoipa::ToSchema;
+
+pub const CREATED: &str = "created";
+pub const UPDATED: &str = "updated";
+pub const WORD_ADDED: &str = "word_added";
+pub const WORD_REMOVED: &str = "word_removed";
+pub const MEMBER_JOINED: &str = "member_joined";
+pub const MEMBER_REMOVED: &str = "member_removed";
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct Activity {
+    pub id: i64,
+    pub collection_id: i64,
+    pub user_id: Option<i64>,
+    pub user_name: Option<String>,
+    pub action: String,
+    pub word_id: Option<i64>,
+    pub word: Option<String>,
+    pub created_at: String,
+}
+
+pub struct ActivityController<'a> {
+    connection: &'a SqlitePool,
+}
+
+impl<'a> ActivityController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        ActivityController { connection }
+    }
+
+    pub async fn log(
+        &self,
+        collection_id: i64,
+        user_id: Option<i64>,
+        action: &str,
+        word_id: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO collection_activity (collection_id, user_id, action, word_id) VALUES (?, ?, ?, ?)",
+        )
+        .bind(collection_id)
+        .bind(user_id)
+        .bind(action)
+        .bind(word_id)
+        .execute(self.connection)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Newest entries first.
+    pub async fn get_for_collection(
+        &self,
+        collection_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Activity>, sqlx::Error> {
+        let records = sqlx::query_as::<_, Activity>(
+            "SELECT a.id, a.collection_id, a.user_id, u.name as user_name, a.action, a.word_id, w.word, a.created_at FROM collection_activity as a LEFT JOIN users as u ON u.id = a.user_id LEFT JOIN words as w ON w.id = a.word_id WHERE a.collection_id = ? ORDER BY a.id DESC LIMIT ?",
+        )
+        .bind(collection_id)
+        .bind(limit)
+        .fetch_all(self.connection)
+        .await?;
+
+        Ok(records)
+    }
+}
@@ -1,29 +1,106 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sqlx::{prelude::FromRow, sqlite::SqliteRow, Row, SqlitePool};
+use sqlx::{prelude::FromRow, types::Json, QueryBuilder, Sqlite, SqlitePool};
+use utoipa::{IntoParams, ToSchema};
 
-use super::{controller::Controller, words::Word};
-use sqlx::Column;
+use super::{
+    activity::{self, ActivityController},
+    controller::{columns, delete_row, insert_row, update_row, Controller},
+    members::{MembersController, Role},
+    words::{Word, WordFilter, WordsController},
+};
 
-#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
-pub struct RawCollection {
-    id: i64,
-    name: String,
-    description: String,
-}
-
-#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
 pub struct Collection {
-    id: i64,
-    name: String,
-    description: String,
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub source_language: String,
+    pub target_language: String,
+    /// Whether answers spelled in another written standard (e.g. Nynorsk in a
+    /// Bokmål collection) count as correct. They are flagged either way.
+    pub accept_other_standards: bool,
+    /// Makes this a smart collection whose words are whatever currently
+    /// matches the filter, instead of the ones in `collection_words`.
+    #[schema(value_type = Option<WordFilter>)]
+    pub filter: Option<Json<WordFilter>>,
+    /// Collections from before accounts existed, or created anonymously, have
+    /// no owner. Everyone can view and edit them, only administrators manage
+    /// them.
+    pub owner_id: Option<i64>,
+    /// `private`, `unlisted` (anyone with the id) or `public` (listed in discover)
+    pub visibility: String,
+    pub cloned_from: Option<i64>,
+    /// Bumped by the database on every update, including changes to the word list
+    pub version: i64,
+    pub updated_at: Option<String>,
     #[sqlx(skip)]
-    words: Vec<Word>,
+    pub words: Vec<Word>,
+}
+
+#[derive(Debug)]
+pub enum CollectionError {
+    /// The caller's role is below the one the action requires
+    Forbidden(Role),
+    /// The collection is no longer at the version the write was based on
+    Stale,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for CollectionError {
+    fn from(err: sqlx::Error) -> Self {
+        CollectionError::Database(err)
+    }
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct DiscoverEntry {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub owner: Option<String>,
+    pub word_count: i64,
+    pub clones: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, IntoParams)]
+pub struct DiscoverParams {
+    /// Searched for in name and description
+    pub q: Option<String>,
+    pub source_language: Option<String>,
+    pub target_language: Option<String>,
+    /// `popular` (most cloned, the default) or `recent`
+    pub sort: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, ToSchema)]
+pub struct CloneOptions {
+    /// Copy the words themselves instead of sharing them with the original
+    #[serde(default)]
+    pub words: bool,
+    /// Keep image and audio of copied words, otherwise they are left empty
+    #[serde(default)]
+    pub media: bool,
+}
+
+/// Fields editors may change, everything else is left to owners.
+const EDITABLE: &[&str] = &["name", "description", "words"];
+
+#[derive(FromRow)]
+struct CollectionWord {
+    collection_id: i64,
+    #[sqlx(flatten)]
+    word: Word,
 }
 
 pub struct CollectionsController<'a> {
     connection: &'a SqlitePool,
     collection: &'a str,
+    admins: &'a [String],
 }
 
 impl<'a> CollectionsController<'a> {
@@ -31,87 +108,193 @@ impl<'a> CollectionsController<'a> {
         CollectionsController {
             connection,
             collection: "collections",
+            admins: &[],
         }
     }
-}
 
-impl Controller<Collection> for CollectionsController<'_> {
-    fn get_collection(&self) -> &str {
-        self.collection
+    /// Names of the users who own the collections without an owner, see
+    /// [`Self::role_of`]. Nobody does unless given.
+    pub fn with_admins(mut self, admins: &'a [String]) -> Self {
+        self.admins = admins;
+        self
     }
 
-    fn get_connection(&self) -> &SqlitePool {
-        self.connection
+    /// Loads only the collection itself, leaving `words` empty.
+    pub async fn get_info(&self, id: i64) -> Result<Collection, sqlx::Error> {
+        let record = sqlx::query_as::<_, Collection>(
+            "SELECT * FROM collections WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_one(self.connection)
+        .await?;
+
+        Ok(record)
     }
 
-    async fn get_all(&self) -> Result<Vec<Collection>, sqlx::Error> {
-        let records = sqlx::query(
-            "SELECT c.id, c.name, c.description, w.id as word_id, w.word, w.translation, w.image, w.audio FROM collections as c LEFT JOIN collection_words ON c.id = collection_words.collection_id LEFT JOIN words as w ON collection_words.word_id = w.id",
-        ).fetch_all(self.connection).await?;
+    /// Collections without an owner plus the ones `user_id` is a member of.
+    pub async fn get_all_for(&self, user_id: Option<i64>) -> Result<Vec<Collection>, sqlx::Error> {
+        let member_of = match user_id {
+            Some(user_id) => {
+                MembersController::new(self.connection)
+                    .collection_ids(user_id)
+                    .await?
+            }
+            None => vec![],
+        };
+
+        let mut collections = self.get_all().await?;
 
-        Ok(collect_collections(records))
+        collections.retain(|collection| {
+            collection.owner_id.is_none() || member_of.contains(&collection.id)
+        });
+
+        Ok(collections)
     }
 
-    async fn get_one(&self, id: i64) -> Result<Collection, sqlx::Error> {
-        let records = sqlx::query(
-            "SELECT c.id, c.name, c.description, w.id as word_id, w.word, w.translation, w.image, w.audio FROM collections as c LEFT JOIN collection_words ON c.id = collection_words.collection_id LEFT JOIN words as w ON collection_words.word_id = w.id WHERE c.id = ?",
-        ).bind(id).fetch_all(self.connection).await?;
+    /// The role `user_id` has on a collection. Anyone can edit collections
+    /// without an owner but only administrators own them, non-private ones can
+    /// be viewed by anyone.
+    pub async fn role_of(
+        &self,
+        collection: &Collection,
+        user_id: Option<i64>,
+    ) -> Result<Option<Role>, sqlx::Error> {
+        if collection.owner_id.is_none() {
+            return Ok(Some(match self.is_admin(user_id).await? {
+                true => Role::Owner,
+                false => Role::Editor,
+            }));
+        }
+
+        let role = match user_id {
+            Some(user_id) => {
+                MembersController::new(self.connection)
+                    .get_role(collection.id, user_id)
+                    .await?
+            }
+            None => None,
+        };
 
-        Ok(collect_collections(records).pop().unwrap())
+        if role.is_none() && collection.visibility != "private" {
+            return Ok(Some(Role::Viewer));
+        }
+
+        Ok(role)
     }
 
-    async fn create(&self, collection: Value) -> Result<i64, sqlx::Error> {
-        let object = collection.as_object().unwrap();
-
-        let query_str = &format!(
-            "INSERT INTO collections ({}) VALUES ({})",
-            object
-                .keys()
-                .map(String::as_str)
-                .filter(|key| *key != "words")
-                .collect::<Vec<_>>()
-                .join(", "),
-            object
-                .keys()
-                .filter(|key| *key != "words")
-                .map(|_| "?")
-                .collect::<Vec<_>>()
-                .join(", ")
-        );
+    async fn is_admin(&self, user_id: Option<i64>) -> Result<bool, sqlx::Error> {
+        let (Some(user_id), false) = (user_id, self.admins.is_empty()) else {
+            return Ok(false);
+        };
+
+        let name: Option<String> = sqlx::query_scalar("SELECT name FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(self.connection)
+            .await?;
+
+        Ok(name.is_some_and(|name| self.admins.contains(&name)))
+    }
 
-        let mut record = sqlx::query(query_str);
+    pub async fn authorize(
+        &self,
+        collection: &Collection,
+        user_id: Option<i64>,
+        required: Role,
+    ) -> Result<Role, CollectionError> {
+        match self.role_of(collection, user_id).await? {
+            Some(role) if role >= required => Ok(role),
+            _ => Err(CollectionError::Forbidden(required)),
+        }
+    }
 
-        for (key, value) in object {
-            if value.is_string() && key != "words" {
-                record = record.bind(value.as_str().unwrap());
-                continue;
+    /// Creates a collection owned by `user_id`, or by nobody when not given.
+    pub async fn create_as(&self, user_id: Option<i64>, item: Value) -> Result<i64, sqlx::Error> {
+        let id = self.insert(&item, user_id).await?;
+
+        if let Some(user_id) = user_id {
+            MembersController::new(self.connection)
+                .set_role(id, user_id, Role::Owner)
+                .await?;
+        }
+
+        let activities = ActivityController::new(self.connection);
+        activities.log(id, user_id, activity::CREATED, None).await?;
+        self.log_words(id, user_id, &[], &self.get_one(id).await?.words)
+            .await?;
+
+        Ok(id)
+    }
+
+    /// Editors may change name, description and words. Everything else, like
+    /// visibility or languages, is left to owners. Given a version,
+    /// nothing happens unless the collection is still at it.
+    pub async fn update_as(
+        &self,
+        id: i64,
+        user_id: Option<i64>,
+        version: Option<i64>,
+        item: Value,
+    ) -> Result<i64, CollectionError> {
+        let before = self.get_one(id).await?;
+
+        let required = match item.as_object() {
+            Some(fields) if fields.keys().all(|key| EDITABLE.contains(&key.as_str())) => {
+                Role::Editor
             }
+            _ => Role::Owner,
+        };
 
-            record = record.bind(value);
+        self.authorize(&before, user_id, required).await?;
+
+        if !self.update_at(id, version, item).await? {
+            return Err(CollectionError::Stale);
         }
 
-        let record = record.execute(self.get_connection()).await?;
+        let after = self.get_one(id).await?;
+
+        ActivityController::new(self.connection)
+            .log(id, user_id, activity::UPDATED, None)
+            .await?;
+        self.log_words(id, user_id, &before.words, &after.words)
+            .await?;
 
-        let row_id = record.last_insert_rowid();
+        Ok(id)
+    }
 
-        if !object.contains_key("words") {
-            return Ok(row_id);
+    pub async fn delete_as(
+        &self,
+        id: i64,
+        user_id: Option<i64>,
+        version: Option<i64>,
+    ) -> Result<u64, CollectionError> {
+        let collection = self.get_info(id).await?;
+
+        self.authorize(&collection, user_id, Role::Owner).await?;
+
+        match delete_row(self.connection, "collections", id, true, version).await? {
+            0 if version.is_some() => Err(CollectionError::Stale),
+            deleted => Ok(deleted),
         }
+    }
 
-        for word in object.get("words").unwrap().as_array().unwrap() {
-            let word_id = {
-                if word.is_i64() {
-                    word.as_i64().unwrap()
-                } else {
-                    word.as_object()
-                        .unwrap()
-                        .get("id")
-                        .unwrap()
-                        .as_i64()
-                        .unwrap()
-                }
-            };
+    /// Inserts a collection with its word list, owned by `owner_id` if given.
+    async fn insert(&self, collection: &Value, owner_id: Option<i64>) -> Result<i64, sqlx::Error> {
+        let mut columns = columns(self.connection, "collections", collection, &["words"]).await?;
+        let word_ids = word_ids(collection)?;
 
+        // The owner is never taken from the body
+        let owner_id = owner_id.map(Value::from);
+        if let Some(owner_id) = &owner_id {
+            columns.push(("owner_id", owner_id));
+        }
+
+        let row_id = insert_row(self.connection, "collections", &columns).await?;
+
+        let Some(word_ids) = word_ids else {
+            return Ok(row_id);
+        };
+
+        for word_id in word_ids {
             let _word_record =
                 sqlx::query("INSERT INTO collection_words (collection_id, word_id) VALUES (?, ?)")
                     .bind(row_id)
@@ -120,59 +303,39 @@ impl Controller<Collection> for CollectionsController<'_> {
                     .await?;
         }
 
+        self.inherit_languages(row_id).await?;
+
         Ok(row_id)
     }
 
-    async fn update(&self, id: i64, item: Value) -> Result<i64, sqlx::Error> {
-        let object = item.as_object().unwrap();
-
-        let query_str = &format!(
-            "UPDATE collections SET {} WHERE id = ?",
-            object
-                .keys()
-                .filter(|key| *key != "words")
-                .map(|key| format!("{} = ?", key))
-                .collect::<Vec<String>>()
-                .join(", ")
-        );
-
-        let mut record = sqlx::query(query_str);
-
-        for (key, value) in object {
-            if value.is_string() {
-                record = record.bind(value.as_str().unwrap());
-                continue;
-            }
-
-            record = record.bind(value);
+    /// Sets the columns and the word list of a collection. Given a version,
+    /// the collection row is claimed first and `false` is returned, with
+    /// nothing changed, if it is no longer at that version.
+    async fn update_at(
+        &self,
+        id: i64,
+        version: Option<i64>,
+        item: Value,
+    ) -> Result<bool, sqlx::Error> {
+        let columns = columns(self.connection, "collections", &item, &["words"]).await?;
+        let word_ids = word_ids(&item)?;
+
+        // A body with only `words` has nothing to set on the row itself, but
+        // still claims the version
+        let changed =
+            update_row(self.connection, "collections", id, &columns, true, version).await?;
+
+        if version.is_some() && changed == 0 {
+            return Ok(false);
         }
 
-        record = record.bind(id);
-
-        let record = record.execute(self.get_connection()).await?;
-
-        let mut existing_words = self.get_one(id).await.unwrap().words;
-
-        if !object.contains_key("words") {
-            return Ok(id);
-        }
+        let mut existing_words = self.get_one(id).await?.words;
 
-        let words = object.get("words").unwrap().as_array().unwrap();
-
-        for word in words {
-            let word_id = {
-                if word.is_i64() {
-                    word.as_i64().unwrap()
-                } else {
-                    word.as_object()
-                        .unwrap()
-                        .get("id")
-                        .unwrap()
-                        .as_i64()
-                        .unwrap()
-                }
-            };
+        let Some(word_ids) = word_ids else {
+            return Ok(true);
+        };
 
+        for word_id in word_ids {
             let existing_word = existing_words.iter().find(|w| w.id == word_id);
 
             match existing_word {
@@ -203,58 +366,299 @@ impl Controller<Collection> for CollectionsController<'_> {
                     .await?;
         }
 
-        Ok(id)
+        self.inherit_languages(id).await?;
+
+        Ok(true)
     }
 
-    async fn delete(&self, id: i64) -> Result<u64, sqlx::Error> {
-        let record = sqlx::query("DELETE FROM collections WHERE id = ?")
-            .bind(id)
-            .execute(self.get_connection())
+    /// Logs which words were added and removed, smart collections are
+    /// skipped as their words change on their own.
+    async fn log_words(
+        &self,
+        id: i64,
+        user_id: Option<i64>,
+        before: &[Word],
+        after: &[Word],
+    ) -> Result<(), sqlx::Error> {
+        if self.get_info(id).await?.filter.is_some() {
+            return Ok(());
+        }
+
+        let activities = ActivityController::new(self.connection);
+
+        for word in after
+            .iter()
+            .filter(|word| !before.iter().any(|w| w.id == word.id))
+        {
+            activities
+                .log(id, user_id, activity::WORD_ADDED, Some(word.id))
+                .await?;
+        }
+
+        for word in before
+            .iter()
+            .filter(|word| !after.iter().any(|w| w.id == word.id))
+        {
+            activities
+                .log(id, user_id, activity::WORD_REMOVED, Some(word.id))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn discover(
+        &self,
+        params: &DiscoverParams,
+    ) -> Result<Vec<DiscoverEntry>, sqlx::Error> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT c.id, c.name, c.description, c.source_language, c.target_language, u.name as owner, (SELECT COUNT(*) FROM collection_words JOIN words ON words.id = collection_words.word_id WHERE collection_id = c.id AND words.deleted_at IS NULL) as word_count, (SELECT COUNT(*) FROM collections as clones WHERE clones.cloned_from = c.id) as clones FROM collections as c LEFT JOIN users as u ON u.id = c.owner_id WHERE c.visibility = 'public' AND c.deleted_at IS NULL",
+        );
+
+        if let Some(q) = &params.q {
+            // The search term is matched literally, wildcards included
+            let escaped = q
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            let pattern = format!("%{}%", escaped);
+
+            query
+                .push(" AND (c.name LIKE ")
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\' OR c.description LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\')");
+        }
+
+        if let Some(source_language) = &params.source_language {
+            query
+                .push(" AND c.source_language = ")
+                .push_bind(source_language.clone());
+        }
+
+        if let Some(target_language) = &params.target_language {
+            query
+                .push(" AND c.target_language = ")
+                .push_bind(target_language.clone());
+        }
+
+        match params.sort.as_deref() {
+            Some("recent") => query.push(" ORDER BY c.id DESC"),
+            _ => query.push(" ORDER BY clones DESC, c.id DESC"),
+        };
+
+        query
+            .push(" LIMIT ")
+            .push_bind(params.limit.unwrap_or(50).clamp(1, 200))
+            .push(" OFFSET ")
+            .push_bind(params.offset.unwrap_or(0).max(0));
+
+        let records = query
+            .build_query_as::<DiscoverEntry>()
+            .fetch_all(self.connection)
             .await?;
 
-        Ok(record.rows_affected())
+        Ok(records)
+    }
+
+    /// Copies a collection into a new private one owned by `owner_id`, in one
+    /// transaction. Smart collections only copy their filter.
+    pub async fn clone_collection(
+        &self,
+        id: i64,
+        owner_id: i64,
+        options: &CloneOptions,
+    ) -> Result<i64, sqlx::Error> {
+        let source = self.get_one(id).await?;
+
+        let mut transaction = self.connection.begin().await?;
+
+        let clone_id = sqlx::query(
+            "INSERT INTO collections (name, description, source_language, target_language, accept_other_standards, filter, owner_id, visibility, cloned_from) SELECT name, description, source_language, target_language, accept_other_standards, filter, ?, 'private', id FROM collections WHERE id = ?",
+        )
+        .bind(owner_id)
+        .bind(id)
+        .execute(&mut *transaction)
+        .await?
+        .last_insert_rowid();
+
+        sqlx::query(
+            "INSERT INTO collection_members (collection_id, user_id, role) VALUES (?, ?, 'owner')",
+        )
+        .bind(clone_id)
+        .bind(owner_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        if source.filter.is_some() {
+            transaction.commit().await?;
+            return Ok(clone_id);
+        }
+
+        for word in &source.words {
+            let word_id = if options.words {
+                let word_id = sqlx::query(
+                    "INSERT INTO words (word, translation, image, audio, source_language, target_language, part_of_speech, difficulty) SELECT word, translation, CASE WHEN ?1 THEN image ELSE '' END, CASE WHEN ?1 THEN audio ELSE '' END, source_language, target_language, part_of_speech, difficulty FROM words WHERE id = ?2",
+                )
+                .bind(options.media)
+                .bind(word.id)
+                .execute(&mut *transaction)
+                .await?
+                .last_insert_rowid();
+
+                sqlx::query("INSERT INTO word_spellings (word_id, language, spelling) SELECT ?, language, spelling FROM word_spellings WHERE word_id = ?")
+                    .bind(word_id)
+                    .bind(word.id)
+                    .execute(&mut *transaction)
+                    .await?;
+
+                sqlx::query("INSERT INTO word_tags (word_id, tag_id) SELECT ?, tag_id FROM word_tags WHERE word_id = ?")
+                    .bind(word_id)
+                    .bind(word.id)
+                    .execute(&mut *transaction)
+                    .await?;
+
+                word_id
+            } else {
+                word.id
+            };
+
+            sqlx::query("INSERT INTO collection_words (collection_id, word_id) VALUES (?, ?)")
+                .bind(clone_id)
+                .bind(word_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(clone_id)
+    }
+
+    async fn resolve_filter(&self, collection: &mut Collection) -> Result<(), sqlx::Error> {
+        if let Some(filter) = &collection.filter {
+            collection.words = WordsController::new(self.connection)
+                .get_filtered(filter)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Words without languages of their own take them from the collection
+    /// they were added to.
+    async fn inherit_languages(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE words SET source_language = (SELECT source_language FROM collections WHERE id = ?1), target_language = (SELECT target_language FROM collections WHERE id = ?1) WHERE target_language IS NULL AND id IN (SELECT word_id FROM collection_words WHERE collection_id = ?1)",
+        )
+        .bind(id)
+        .execute(self.connection)
+        .await?;
+
+        Ok(())
     }
 }
 
-fn collect_collections(rows: Vec<SqliteRow>) -> Vec<Collection> {
-    let mut collections: Vec<Collection> = vec![];
-
-    for row in rows {
-        let id: i64 = row.try_get("id").unwrap();
-        let name: String = row.try_get("name").unwrap();
-        let description: String = row.try_get("description").unwrap();
-        let word_id: i64 = row.try_get("word_id").unwrap();
-        let word: String = row.try_get("word").unwrap();
-        let translation: String = row.try_get("translation").unwrap();
-        let image: String = row.try_get("image").unwrap();
-        let audio: String = row.try_get("audio").unwrap();
-
-        let collection = collections.iter_mut().find(|c| c.id == id);
-
-        match collection {
-            Some(collection) => {
-                collection.words.push(Word {
-                    id: word_id,
-                    word,
-                    translation,
-                    image,
-                    audio,
-                });
-            }
-            None => {
-                collections.push(Collection {
-                    id,
-                    name: name.clone(),
-                    description: description.clone(),
-                    words: vec![Word {
-                        id: word_id,
-                        word,
-                        translation,
-                        image,
-                        audio,
-                    }],
-                });
-            }
+impl Controller<Collection> for CollectionsController<'_> {
+    fn get_collection(&self) -> &str {
+        self.collection
+    }
+
+    fn get_connection(&self) -> &SqlitePool {
+        self.connection
+    }
+
+    fn soft_deletes(&self) -> bool {
+        true
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(table = "collections"))]
+    async fn get_all(&self) -> Result<Vec<Collection>, sqlx::Error> {
+        let collections =
+            sqlx::query_as::<_, Collection>("SELECT * FROM collections WHERE deleted_at IS NULL")
+                .fetch_all(self.connection)
+                .await?;
+
+        let words = sqlx::query_as::<_, CollectionWord>(
+            "SELECT collection_words.collection_id, w.* FROM collection_words JOIN words as w ON collection_words.word_id = w.id WHERE w.deleted_at IS NULL",
+        )
+        .fetch_all(self.connection)
+        .await?;
+
+        let mut collections = collect_collections(collections, words);
+
+        for collection in collections.iter_mut() {
+            self.resolve_filter(collection).await?;
+        }
+
+        Ok(collections)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(table = "collections", id = id))]
+    async fn get_one(&self, id: i64) -> Result<Collection, sqlx::Error> {
+        let collection = self.get_info(id).await?;
+
+        let words = sqlx::query_as::<_, CollectionWord>(
+            "SELECT collection_words.collection_id, w.* FROM collection_words JOIN words as w ON collection_words.word_id = w.id WHERE collection_words.collection_id = ? AND w.deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_all(self.connection)
+        .await?;
+
+        let mut collection = collect_collections(vec![collection], words).pop().unwrap();
+
+        self.resolve_filter(&mut collection).await?;
+
+        Ok(collection)
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(table = "collections"))]
+    async fn create(&self, collection: Value) -> Result<i64, sqlx::Error> {
+        self.insert(&collection, None).await
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(table = "collections", id = id))]
+    async fn update(&self, id: i64, item: Value) -> Result<i64, sqlx::Error> {
+        self.update_at(id, None, item).await?;
+
+        Ok(id)
+    }
+}
+
+/// Ids in the `words` list of a collection body, given as ids or as words.
+/// `None` if the body leaves the word list alone.
+fn word_ids(item: &Value) -> Result<Option<Vec<i64>>, sqlx::Error> {
+    let Some(words) = item.get("words") else {
+        return Ok(None);
+    };
+
+    let invalid = || sqlx::Error::Encode("words must be a list of word ids or words".into());
+
+    words
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|word| {
+            word.as_i64()
+                .or_else(|| word.get("id").and_then(Value::as_i64))
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+fn collect_collections(
+    mut collections: Vec<Collection>,
+    words: Vec<CollectionWord>,
+) -> Vec<Collection> {
+    for CollectionWord {
+        collection_id,
+        word,
+    } in words
+    {
+        if let Some(collection) = collections.iter_mut().find(|c| c.id == collection_id) {
+            collection.words.push(word);
         }
     }
 
@@ -1,7 +1,252 @@
-use axum::Json;
+use std::fmt;
+
 use serde_json::Value;
-use sqlx::{query, query_as, Database, FromRow, Sqlite, SqlitePool};
+use sqlx::{
+    query, query::Query, query_as, sqlite::SqliteArguments, Database, FromRow, Sqlite, SqlitePool,
+};
+
+/// Binds a JSON value with its native SQLite type. Arrays and objects are
+/// stored as JSON text.
+pub fn bind_value<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: &'q Value,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(value) => query.bind(*value),
+        Value::Number(number) => match number.as_i64() {
+            Some(number) => query.bind(number),
+            None => query.bind(number.as_f64()),
+        },
+        Value::String(value) => query.bind(value.as_str()),
+        Value::Array(_) | Value::Object(_) => query.bind(value),
+    }
+}
+
+/// Columns kept by the server and the database, never taken from a body.
+pub const SYSTEM_COLUMNS: [&str; 5] = ["id", "version", "updated_at", "deleted_at", "owner_id"];
+
+/// A body tried to set one of the [`SYSTEM_COLUMNS`]. Raised wrapped in
+/// `sqlx::Error::Encode`.
+#[derive(Debug)]
+pub struct SystemColumn(pub String);
+
+impl fmt::Display for SystemColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is maintained by the server and can't be set", self.0)
+    }
+}
+
+impl std::error::Error for SystemColumn {}
+
+/// The column values of a JSON object. Keys end up in the SQL text, so each
+/// one has to be a column of `table`, otherwise `ColumnNotFound` is returned.
+/// Values SQLite would convert to another type are refused with `Encode`,
+/// so every row reads back as it was written, and so are the
+/// [`SYSTEM_COLUMNS`]. Keys in `skip` are left to the caller.
+pub async fn columns<'a>(
+    connection: &SqlitePool,
+    table: &str,
+    item: &'a Value,
+    skip: &[&str],
+) -> Result<Vec<(&'a str, &'a Value)>, sqlx::Error> {
+    let object = item
+        .as_object()
+        .ok_or_else(|| sqlx::Error::Encode("expected an object of column values".into()))?;
+
+    if let Some(key) = object
+        .keys()
+        .find(|key| SYSTEM_COLUMNS.contains(&key.as_str()))
+    {
+        return Err(sqlx::Error::Encode(Box::new(SystemColumn(key.clone()))));
+    }
+
+    let declared: Vec<(String, String)> = query_as("SELECT name, type FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(connection)
+        .await?;
+
+    object
+        .iter()
+        .filter(|(key, _)| !skip.contains(&key.as_str()))
+        .map(|(key, value)| {
+            let (_, declared) = declared
+                .iter()
+                .find(|(column, _)| column == key)
+                .ok_or_else(|| sqlx::Error::ColumnNotFound(key.clone()))?;
+
+            if !fits(declared, value) {
+                return Err(sqlx::Error::Encode(
+                    format!("{} takes {} values, not {}", key, declared, kind(value)).into(),
+                ));
+            }
+
+            Ok((key.as_str(), value))
+        })
+        .collect()
+}
+
+/// Whether a column declared as `declared` stores `value` as is. Follows the
+/// rules SQLite derives column affinity by, with dates kept as text.
+fn fits(declared: &str, value: &Value) -> bool {
+    let declared = declared.to_ascii_uppercase();
+    let declares = |names: &[&str]| names.iter().any(|name| declared.contains(name));
+
+    match value {
+        Value::Null => true,
+        _ if declared.is_empty() || declares(&["BLOB"]) => true,
+        Value::Bool(_) => declares(&["INT", "BOOL"]),
+        Value::Number(number) if number.is_i64() => {
+            declares(&["INT", "BOOL", "REAL", "FLOA", "DOUB", "NUMERIC", "DECIMAL"])
+        }
+        Value::Number(_) => declares(&["REAL", "FLOA", "DOUB", "NUMERIC", "DECIMAL"]),
+        Value::String(_) if declares(&["CHAR", "CLOB", "TEXT"]) => true,
+        // Numeric affinity turns text that looks like a number into one
+        Value::String(text) => declares(&["DATE", "TIME"]) && text.trim().parse::<f64>().is_err(),
+        Value::Array(_) | Value::Object(_) => declares(&["CHAR", "CLOB", "TEXT"]),
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(number) if number.is_i64() => "an integer",
+        Value::Number(_) => "a number",
+        Value::String(_) => "text",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Inserts a row with the given columns, the table's defaults if there are none.
+pub async fn insert_row(
+    connection: &SqlitePool,
+    table: &str,
+    columns: &[(&str, &Value)],
+) -> Result<i64, sqlx::Error> {
+    let query_str = match columns.is_empty() {
+        true => format!("INSERT INTO {} DEFAULT VALUES", table),
+        false => format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            columns
+                .iter()
+                .map(|(column, _)| quote(column))
+                .collect::<Vec<_>>()
+                .join(", "),
+            columns.iter().map(|_| "?").collect::<Vec<_>>().join(", ")
+        ),
+    };
+
+    let mut record = query(&query_str);
+
+    for (_, value) in columns {
+        record = bind_value(record, value);
+    }
+
+    let record = record.execute(connection).await?;
+
+    Ok(record.last_insert_rowid())
+}
+
+/// Sets the given columns of a row, returning how many rows were changed.
+/// Given a `version`, the row is only changed if it is still at that version,
+/// so of two writes based on the same copy only the first one goes through.
+/// Without columns there is nothing to set, but the version is still claimed.
+pub async fn update_row(
+    connection: &SqlitePool,
+    table: &str,
+    id: i64,
+    columns: &[(&str, &Value)],
+    soft_deletes: bool,
+    version: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let assignments = match (columns.is_empty(), version) {
+        (true, None) => return Ok(0),
+        // The version trigger bumps it all the same
+        (true, Some(_)) => "version = version".to_string(),
+        (false, _) => columns
+            .iter()
+            .map(|(column, _)| format!("{} = ?", quote(column)))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    let query_str = format!(
+        "UPDATE {} SET {} WHERE id = ?{}{}",
+        table,
+        assignments,
+        scope(soft_deletes),
+        at_version(version)
+    );
+
+    let mut record = query(&query_str);
+
+    for (_, value) in columns {
+        record = bind_value(record, value);
+    }
+
+    record = record.bind(id);
+
+    if let Some(version) = version {
+        record = record.bind(version);
+    }
+
+    Ok(record.execute(connection).await?.rows_affected())
+}
 
+/// Deletes a row, or moves it to the trash for tables with soft deletes,
+/// returning how many rows were deleted. Like [`update_row`], a given
+/// `version` has to match.
+pub async fn delete_row(
+    connection: &SqlitePool,
+    table: &str,
+    id: i64,
+    soft_deletes: bool,
+    version: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let query_str = match soft_deletes {
+        true => format!(
+            "UPDATE {} SET deleted_at = CURRENT_TIMESTAMP WHERE id = ?{}{}",
+            table,
+            scope(true),
+            at_version(version)
+        ),
+        false => format!("DELETE FROM {} WHERE id = ?{}", table, at_version(version)),
+    };
+
+    let mut record = query(&query_str).bind(id);
+
+    if let Some(version) = version {
+        record = record.bind(version);
+    }
+
+    Ok(record.execute(connection).await?.rows_affected())
+}
+
+/// Column names come from the schema, quoting keeps keywords usable as names.
+fn quote(column: &str) -> String {
+    format!("\"{}\"", column.replace('"', "\"\""))
+}
+
+fn scope(soft_deletes: bool) -> &'static str {
+    match soft_deletes {
+        true => " AND deleted_at IS NULL",
+        false => "",
+    }
+}
+
+fn at_version(version: Option<i64>) -> &'static str {
+    match version {
+        Some(_) => " AND version = ?",
+        None => "",
+    }
+}
+
+// Only implemented and awaited inside this crate, so the futures never need
+// a `Send` bound spelled out
+#[allow(async_fn_in_trait)]
 pub trait Controller<Item>
 where
     Item: Send + Unpin,
@@ -10,18 +255,31 @@ where
     fn get_collection(&self) -> &str;
     fn get_connection(&self) -> &SqlitePool;
 
+    /// Tables with a `deleted_at` column keep deleted rows in the trash until
+    /// they are purged. Those rows are left out of every query.
+    fn soft_deletes(&self) -> bool {
+        false
+    }
+
+    #[tracing::instrument(level = "debug", skip_all, fields(table = self.get_collection()))]
     async fn get_all(&self) -> Result<Vec<Item>, sqlx::Error> {
-        let records = query_as::<_, Item>(&format!("SELECT * FROM {}", self.get_collection()))
-            .fetch_all(self.get_connection())
-            .await?;
+        let records = query_as::<_, Item>(&format!(
+            "SELECT * FROM {} WHERE 1 = 1{}",
+            self.get_collection(),
+            scope(self.soft_deletes())
+        ))
+        .fetch_all(self.get_connection())
+        .await?;
 
         Ok(records)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(table = self.get_collection(), id = id))]
     async fn get_one(&self, id: i64) -> Result<Item, sqlx::Error> {
         let record = query_as::<_, Item>(&format!(
-            "SELECT * FROM {} WHERE id = ?",
-            self.get_collection()
+            "SELECT * FROM {} WHERE id = ?{}",
+            self.get_collection(),
+            scope(self.soft_deletes())
         ))
         .bind(id)
         .fetch_one(self.get_connection())
@@ -30,77 +288,40 @@ where
         Ok(record)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(table = self.get_collection()))]
     async fn create(&self, item: Value) -> Result<i64, sqlx::Error> {
-        let object = item.as_object().unwrap();
+        let columns = columns(self.get_connection(), self.get_collection(), &item, &[]).await?;
 
-        let query_str = &format!(
-            "INSERT INTO {} ({}) VALUES ({})",
-            self.get_collection(),
-            object
-                .keys()
-                .map(String::as_str)
-                .collect::<Vec<_>>()
-                .join(", "),
-            object.keys().map(|_| "?").collect::<Vec<_>>().join(", ")
-        );
-
-        let mut record = query(query_str);
-
-        for (key, value) in object {
-            if value.is_string() {
-                record = record.bind(value.as_str().unwrap());
-                continue;
-            }
-
-            record = record.bind(value);
-        }
-
-        let record = record.execute(self.get_connection()).await?;
-
-        Ok(record.last_insert_rowid())
+        insert_row(self.get_connection(), self.get_collection(), &columns).await
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(table = self.get_collection(), id = id))]
     async fn update(&self, id: i64, item: Value) -> Result<i64, sqlx::Error> {
-        let object = item.as_object().unwrap();
+        let columns = columns(self.get_connection(), self.get_collection(), &item, &[]).await?;
 
-        let query_str = &format!(
-            "UPDATE {} SET {} WHERE id = ?",
+        update_row(
+            self.get_connection(),
             self.get_collection(),
-            object
-                .iter()
-                .map(|(key, value)| format!("{} = ?", key))
-                .collect::<Vec<String>>()
-                .join(", ")
-        );
-
-        let mut record = query(query_str);
-
-        for (key, value) in object {
-            if value.is_string() {
-                record = record.bind(value.as_str().unwrap());
-                continue;
-            }
-
-            record = record.bind(value);
-        }
-
-        record = record.bind(id);
-
-        let record = record.execute(self.get_connection()).await?;
+            id,
+            &columns,
+            self.soft_deletes(),
+            None,
+        )
+        .await?;
 
         Ok(id)
     }
 
+    #[tracing::instrument(level = "debug", skip_all, fields(table = self.get_collection(), id = id))]
     async fn delete(&self, id: i64) -> Result<u64, sqlx::Error> {
-        let record = query(&format!(
-            "DELETE FROM {} WHERE id = ?",
-            self.get_collection()
-        ))
-        .bind(id)
-        .execute(self.get_connection())
-        .await?;
-
-        Ok(record.rows_affected())
+        delete_row(
+            self.get_connection(),
+            self.get_collection(),
+            id,
+            self.soft_deletes(),
+            None,
+        )
+        .await
     }
 }
 
@@ -0,0 +1,510 @@
+use std::{collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, types::Json, Sqlite, SqlitePool, Transaction};
+use utoipa::ToSchema;
+
+use super::{
+    collections::Collection,
+    members::Role,
+    reviews::Review,
+    words::{Spelling, Word, WordFilter},
+};
+
+/// Bumped whenever the layout of `Dump` changes in a way older importers
+/// can't read.
+pub const FORMAT_VERSION: i64 = 1;
+
+/// Everything needed to move an instance, trash excluded. Ids are the ones of
+/// the exporting database and only used to connect the parts on import.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Dump {
+    pub format_version: i64,
+    pub exported_at: String,
+    pub users: Vec<DumpUser>,
+    /// Names of all tags, including the ones no word has
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub words: Vec<DumpWord>,
+    pub collections: Vec<DumpCollection>,
+    pub reviews: Vec<Review>,
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct DumpUser {
+    pub id: i64,
+    pub name: String,
+    pub password_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct DumpWord {
+    #[serde(flatten)]
+    pub word: Word,
+    pub spellings: Vec<Spelling>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct DumpCollection {
+    pub id: i64,
+    pub name: String,
+    pub description: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub accept_other_standards: bool,
+    #[schema(value_type = Option<WordFilter>)]
+    pub filter: Option<Json<WordFilter>>,
+    pub owner_id: Option<i64>,
+    pub visibility: String,
+    pub cloned_from: Option<i64>,
+    pub word_ids: Vec<i64>,
+    pub members: Vec<DumpMember>,
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct DumpMember {
+    pub user_id: i64,
+    pub role: Role,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Adds everything under new ids. Users and tags are matched by name.
+    #[default]
+    Merge,
+    /// Keeps the original ids, only allowed while there are no words or
+    /// collections. Users are still matched by name, so the importing admin
+    /// can exist already.
+    Restore,
+}
+
+#[derive(Serialize, Clone, Debug, Default, ToSchema)]
+pub struct ImportSummary {
+    pub users: usize,
+    pub words: usize,
+    pub collections: usize,
+    pub reviews: usize,
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    UnsupportedVersion(i64),
+    NotEmpty,
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::UnsupportedVersion(version) => {
+                write!(f, "exports of format version {} are not supported", version)
+            }
+            ImportError::NotEmpty => write!(f, "restoring requires an empty database"),
+            ImportError::Database(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<sqlx::Error> for ImportError {
+    fn from(err: sqlx::Error) -> Self {
+        ImportError::Database(err)
+    }
+}
+
+/// A row belonging to the word or collection `parent_id`.
+#[derive(FromRow)]
+struct Child<T> {
+    parent_id: i64,
+    #[sqlx(flatten)]
+    value: T,
+}
+
+#[derive(FromRow)]
+struct TagName {
+    name: String,
+}
+
+/// The values of the rows by their parent, in the order they were read.
+fn by_parent<T>(rows: Vec<Child<T>>) -> HashMap<i64, Vec<T>> {
+    let mut parents: HashMap<i64, Vec<T>> = HashMap::new();
+
+    for row in rows {
+        parents.entry(row.parent_id).or_default().push(row.value);
+    }
+
+    parents
+}
+
+#[derive(FromRow)]
+struct WordId {
+    word_id: i64,
+}
+
+/// Old ids of the dump to the ids they got in this database.
+#[derive(Default)]
+struct Ids {
+    users: HashMap<i64, i64>,
+    words: HashMap<i64, i64>,
+    collections: HashMap<i64, i64>,
+}
+
+pub struct DumpController<'a> {
+    connection: &'a SqlitePool,
+}
+
+impl<'a> DumpController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        DumpController { connection }
+    }
+
+    /// Reads everything in one transaction, so the parts of the dump fit
+    /// together even while others keep writing.
+    pub async fn export(&self) -> Result<Dump, sqlx::Error> {
+        let mut transaction = self.connection.begin().await?;
+
+        let users =
+            sqlx::query_as::<_, DumpUser>("SELECT id, name, password_hash FROM users ORDER BY id")
+                .fetch_all(&mut *transaction)
+                .await?;
+
+        let tags = sqlx::query_scalar::<_, String>(
+            "SELECT name FROM tags WHERE deleted_at IS NULL ORDER BY name",
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let mut spellings = by_parent(
+            sqlx::query_as::<_, Child<Spelling>>(
+                "SELECT word_id as parent_id, language, spelling FROM word_spellings ORDER BY word_id, language",
+            )
+            .fetch_all(&mut *transaction)
+            .await?,
+        );
+
+        let mut word_tags = by_parent(
+            sqlx::query_as::<_, Child<TagName>>(
+                "SELECT word_tags.word_id as parent_id, tags.name FROM word_tags JOIN tags ON tags.id = word_tags.tag_id WHERE tags.deleted_at IS NULL ORDER BY tags.name",
+            )
+            .fetch_all(&mut *transaction)
+            .await?,
+        );
+
+        let words =
+            sqlx::query_as::<_, Word>("SELECT * FROM words WHERE deleted_at IS NULL ORDER BY id")
+                .fetch_all(&mut *transaction)
+                .await?
+                .into_iter()
+                .map(|word| DumpWord {
+                    spellings: spellings.remove(&word.id).unwrap_or_default(),
+                    tags: word_tags
+                        .remove(&word.id)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|tag| tag.name)
+                        .collect(),
+                    word,
+                })
+                .collect::<Vec<_>>();
+
+        let mut members = by_parent(
+            sqlx::query_as::<_, Child<DumpMember>>(
+                "SELECT collection_id as parent_id, user_id, role FROM collection_members",
+            )
+            .fetch_all(&mut *transaction)
+            .await?,
+        );
+
+        let mut collection_words = by_parent(
+            sqlx::query_as::<_, Child<WordId>>(
+                "SELECT collection_words.collection_id as parent_id, collection_words.word_id FROM collection_words JOIN words ON words.id = collection_words.word_id WHERE words.deleted_at IS NULL ORDER BY collection_words.id",
+            )
+            .fetch_all(&mut *transaction)
+            .await?,
+        );
+
+        let collections = sqlx::query_as::<_, Collection>(
+            "SELECT * FROM collections WHERE deleted_at IS NULL ORDER BY id",
+        )
+        .fetch_all(&mut *transaction)
+        .await?
+        .into_iter()
+        .map(|collection| {
+            let words = collection_words.remove(&collection.id).unwrap_or_default();
+            let members = members.remove(&collection.id).unwrap_or_default();
+
+            dump_collection(collection, words, members)
+        })
+        .collect();
+
+        let reviews = sqlx::query_as::<_, Review>(
+            "SELECT reviews.* FROM reviews JOIN words ON words.id = reviews.word_id WHERE words.deleted_at IS NULL ORDER BY reviews.id",
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+
+        let exported_at = sqlx::query_scalar("SELECT datetime('now')")
+            .fetch_one(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(Dump {
+            format_version: FORMAT_VERSION,
+            exported_at,
+            users,
+            tags,
+            words,
+            collections,
+            reviews,
+        })
+    }
+
+    /// Imports a dump in one transaction, nothing is written if any part
+    /// fails.
+    pub async fn import(
+        &self,
+        dump: &Dump,
+        mode: ImportMode,
+    ) -> Result<ImportSummary, ImportError> {
+        if dump.format_version > FORMAT_VERSION {
+            return Err(ImportError::UnsupportedVersion(dump.format_version));
+        }
+
+        let mut transaction = self.connection.begin().await?;
+
+        if mode == ImportMode::Restore {
+            let rows: i64 = sqlx::query_scalar(
+                "SELECT (SELECT COUNT(*) FROM words) + (SELECT COUNT(*) FROM collections)",
+            )
+            .fetch_one(&mut *transaction)
+            .await?;
+
+            if rows > 0 {
+                return Err(ImportError::NotEmpty);
+            }
+        }
+
+        let keep_ids = mode == ImportMode::Restore;
+        let mut ids = Ids::default();
+        let mut summary = ImportSummary::default();
+
+        for user in &dump.users {
+            let existing: Option<i64> = sqlx::query_scalar("SELECT id FROM users WHERE name = ?")
+                .bind(&user.name)
+                .fetch_optional(&mut *transaction)
+                .await?;
+
+            let id = match existing {
+                Some(id) => id,
+                None => {
+                    summary.users += 1;
+
+                    // The importing admin may already hold the original id
+                    sqlx::query(
+                        "INSERT INTO users (id, name, password_hash) VALUES ((SELECT ?1 WHERE NOT EXISTS (SELECT 1 FROM users WHERE id = ?1)), ?2, ?3)",
+                    )
+                        .bind(keep_ids.then_some(user.id))
+                        .bind(&user.name)
+                        .bind(&user.password_hash)
+                        .execute(&mut *transaction)
+                        .await?
+                        .last_insert_rowid()
+                }
+            };
+
+            ids.users.insert(user.id, id);
+        }
+
+        for name in &dump.tags {
+            import_tag(&mut transaction, name).await?;
+        }
+
+        for DumpWord {
+            word,
+            spellings,
+            tags,
+        } in &dump.words
+        {
+            let id = sqlx::query(
+                "INSERT INTO words (id, word, translation, image, audio, source_language, target_language, part_of_speech, difficulty) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(keep_ids.then_some(word.id))
+            .bind(&word.word)
+            .bind(&word.translation)
+            .bind(&word.image)
+            .bind(&word.audio)
+            .bind(&word.source_language)
+            .bind(&word.target_language)
+            .bind(&word.part_of_speech)
+            .bind(word.difficulty)
+            .execute(&mut *transaction)
+            .await?
+            .last_insert_rowid();
+
+            for spelling in spellings {
+                sqlx::query(
+                    "INSERT INTO word_spellings (word_id, language, spelling) VALUES (?, ?, ?)",
+                )
+                .bind(id)
+                .bind(&spelling.language)
+                .bind(&spelling.spelling)
+                .execute(&mut *transaction)
+                .await?;
+            }
+
+            import_tags(&mut transaction, id, tags).await?;
+
+            ids.words.insert(word.id, id);
+            summary.words += 1;
+        }
+
+        for collection in &dump.collections {
+            let id = sqlx::query(
+                "INSERT INTO collections (id, name, description, source_language, target_language, accept_other_standards, filter, owner_id, visibility) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(keep_ids.then_some(collection.id))
+            .bind(&collection.name)
+            .bind(&collection.description)
+            .bind(&collection.source_language)
+            .bind(&collection.target_language)
+            .bind(collection.accept_other_standards)
+            .bind(&collection.filter)
+            .bind(collection.owner_id.and_then(|id| ids.users.get(&id)))
+            .bind(&collection.visibility)
+            .execute(&mut *transaction)
+            .await?
+            .last_insert_rowid();
+
+            for word_id in collection
+                .word_ids
+                .iter()
+                .filter_map(|id| ids.words.get(id))
+            {
+                sqlx::query("INSERT INTO collection_words (collection_id, word_id) VALUES (?, ?)")
+                    .bind(id)
+                    .bind(word_id)
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+
+            for member in &collection.members {
+                let Some(user_id) = ids.users.get(&member.user_id) else {
+                    continue;
+                };
+
+                sqlx::query(
+                    "INSERT OR IGNORE INTO collection_members (collection_id, user_id, role) VALUES (?, ?, ?)",
+                )
+                .bind(id)
+                .bind(user_id)
+                .bind(member.role)
+                .execute(&mut *transaction)
+                .await?;
+            }
+
+            ids.collections.insert(collection.id, id);
+            summary.collections += 1;
+        }
+
+        // Clones may come before their original in the dump
+        for collection in &dump.collections {
+            let original = collection
+                .cloned_from
+                .and_then(|id| ids.collections.get(&id));
+
+            if let Some(original) = original {
+                sqlx::query("UPDATE collections SET cloned_from = ? WHERE id = ?")
+                    .bind(original)
+                    .bind(ids.collections[&collection.id])
+                    .execute(&mut *transaction)
+                    .await?;
+            }
+        }
+
+        for review in &dump.reviews {
+            let Some(word_id) = ids.words.get(&review.word_id) else {
+                continue;
+            };
+
+            sqlx::query(
+                "INSERT INTO reviews (word_id, user_id, correct, box, due_at, created_at) VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(word_id)
+            .bind(review.user_id.and_then(|id| ids.users.get(&id)))
+            .bind(review.correct)
+            .bind(review.r#box)
+            .bind(&review.due_at)
+            .bind(&review.created_at)
+            .execute(&mut *transaction)
+            .await?;
+
+            summary.reviews += 1;
+        }
+
+        transaction.commit().await?;
+
+        Ok(summary)
+    }
+}
+
+fn dump_collection(
+    collection: Collection,
+    words: Vec<WordId>,
+    members: Vec<DumpMember>,
+) -> DumpCollection {
+    DumpCollection {
+        // Smart collections get their words from the filter on import
+        word_ids: match collection.filter {
+            Some(_) => vec![],
+            None => words.into_iter().map(|word| word.word_id).collect(),
+        },
+        members,
+        id: collection.id,
+        name: collection.name,
+        description: collection.description,
+        source_language: collection.source_language,
+        target_language: collection.target_language,
+        accept_other_standards: collection.accept_other_standards,
+        filter: collection.filter,
+        owner_id: collection.owner_id,
+        visibility: collection.visibility,
+        cloned_from: collection.cloned_from,
+    }
+}
+
+/// Tags are matched by name, so merging reuses the tags already there.
+async fn import_tag(
+    transaction: &mut Transaction<'_, Sqlite>,
+    name: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tags (name) VALUES (?) ON CONFLICT (name) DO UPDATE SET deleted_at = NULL",
+    )
+    .bind(name)
+    .execute(&mut **transaction)
+    .await?;
+
+    Ok(())
+}
+
+async fn import_tags(
+    transaction: &mut Transaction<'_, Sqlite>,
+    word_id: i64,
+    names: &[String],
+) -> Result<(), sqlx::Error> {
+    for name in names {
+        import_tag(transaction, name).await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO word_tags (word_id, tag_id) SELECT ?, id FROM tags WHERE name = ?",
+        )
+        .bind(word_id)
+        .bind(name)
+        .execute(&mut **transaction)
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, SqlitePool};
+use utoipa::ToSchema;
+
+use super::controller::Controller;
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+pub struct LlmCall {
+    pub id: i64,
+    pub user_id: Option<i64>,
+    pub model: String,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub latency_ms: i64,
+    pub outcome: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NewLlmCall<'a> {
+    pub user_id: Option<i64>,
+    pub model: &'a str,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub latency_ms: i64,
+    pub outcome: &'a str,
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct LlmDailyStats {
+    pub day: String,
+    pub model: String,
+    pub calls: i64,
+    pub failures: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub avg_latency_ms: f64,
+    #[sqlx(skip)]
+    pub cost: f64,
+}
+
+pub struct LlmCallsController<'a> {
+    connection: &'a SqlitePool,
+    collection: &'a str,
+}
+
+impl<'a> LlmCallsController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        LlmCallsController {
+            connection,
+            collection: "llm_calls",
+        }
+    }
+
+    pub async fn record(&self, call: &NewLlmCall<'_>) -> Result<i64, sqlx::Error> {
+        let record = sqlx::query(
+            "INSERT INTO llm_calls (user_id, model, prompt_tokens, completion_tokens, latency_ms, outcome) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(call.user_id)
+        .bind(call.model)
+        .bind(call.prompt_tokens)
+        .bind(call.completion_tokens)
+        .bind(call.latency_ms)
+        .bind(call.outcome)
+        .execute(self.get_connection())
+        .await?;
+
+        Ok(record.last_insert_rowid())
+    }
+
+    /// Tokens spent since midnight UTC, by everyone or only by `user_id`.
+    pub async fn tokens_today(&self, user_id: Option<i64>) -> Result<i64, sqlx::Error> {
+        let tokens = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(prompt_tokens + completion_tokens), 0) FROM llm_calls WHERE created_at >= date('now') AND (?1 IS NULL OR user_id = ?1)",
+        )
+        .bind(user_id)
+        .fetch_one(self.get_connection())
+        .await?;
+
+        Ok(tokens)
+    }
+
+    pub async fn daily_stats(&self, days: i64) -> Result<Vec<LlmDailyStats>, sqlx::Error> {
+        let records = sqlx::query_as::<_, LlmDailyStats>(
+            "SELECT date(created_at) as day, model, COUNT(*) as calls, SUM(outcome != 'success') as failures, SUM(prompt_tokens) as prompt_tokens, SUM(completion_tokens) as completion_tokens, AVG(latency_ms) as avg_latency_ms FROM llm_calls WHERE created_at >= date('now', '-' || (? - 1) || ' days') GROUP BY day, model ORDER BY day DESC, model",
+        )
+        .bind(days)
+        .fetch_all(self.get_connection())
+        .await?;
+
+        Ok(records)
+    }
+}
+
+impl Controller<LlmCall> for LlmCallsController<'_> {
+    fn get_collection(&self) -> &str {
+        self.collection
+    }
+
+    fn get_connection(&self) -> &SqlitePool {
+        self.connection
+    }
+}
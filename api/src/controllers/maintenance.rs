@@ -0,0 +1,93 @@
+use serde::Serialize;
+use sqlx::{prelude::FromRow, SqlitePool};
+use utoipa::ToSchema;
+
+#[derive(Serialize, FromRow, Clone, Debug, ToSchema)]
+pub struct Checkpoint {
+    /// Whether readers or writers kept the checkpoint from finishing
+    pub busy: bool,
+    /// Pages in the WAL before truncating it
+    pub log: i64,
+    pub checkpointed: i64,
+}
+
+/// Row counts, trashed rows excluded.
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct Stats {
+    pub users: i64,
+    pub words: i64,
+    pub collections: i64,
+    pub tags: i64,
+    pub reviews: i64,
+    /// Words whose latest review is due, or that were never reviewed
+    pub due_words: i64,
+    pub trashed: i64,
+    pub generated_questions: i64,
+}
+
+pub struct MaintenanceController<'a> {
+    connection: &'a SqlitePool,
+}
+
+impl<'a> MaintenanceController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        MaintenanceController { connection }
+    }
+
+    /// Writes a consistent copy of the live database to `path`, which must not
+    /// exist yet. Readers and writers carry on while it runs.
+    pub async fn backup_into(&self, path: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path)
+            .execute(self.connection)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Moves the WAL into the database file and truncates it, so it doesn't
+    /// grow without bounds.
+    pub async fn checkpoint(&self) -> Result<Checkpoint, sqlx::Error> {
+        let record = sqlx::query_as::<_, Checkpoint>("PRAGMA wal_checkpoint(TRUNCATE)")
+            .fetch_one(self.connection)
+            .await?;
+
+        Ok(record)
+    }
+
+    pub async fn stats(&self) -> Result<Stats, sqlx::Error> {
+        let record = sqlx::query_as::<_, Stats>(
+            "SELECT (SELECT COUNT(*) FROM users) as users, (SELECT COUNT(*) FROM words WHERE deleted_at IS NULL) as words, (SELECT COUNT(*) FROM collections WHERE deleted_at IS NULL) as collections, (SELECT COUNT(*) FROM tags WHERE deleted_at IS NULL) as tags, (SELECT COUNT(*) FROM reviews) as reviews, (SELECT COUNT(*) FROM words as w WHERE w.deleted_at IS NULL AND COALESCE((SELECT due_at FROM reviews WHERE reviews.word_id = w.id ORDER BY reviews.id DESC LIMIT 1) <= datetime('now'), TRUE)) as due_words, (SELECT COUNT(*) FROM words WHERE deleted_at IS NOT NULL) + (SELECT COUNT(*) FROM collections WHERE deleted_at IS NOT NULL) + (SELECT COUNT(*) FROM tags WHERE deleted_at IS NOT NULL) as trashed, (SELECT COUNT(*) FROM generated_questions) as generated_questions",
+        )
+        .fetch_one(self.connection)
+        .await?;
+
+        Ok(record)
+    }
+
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(self.connection).await?;
+
+        Ok(())
+    }
+
+    /// Versions of the migrations that ran successfully.
+    pub async fn applied_migrations(&self) -> Result<Vec<i64>, sqlx::Error> {
+        let records = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(self.connection)
+            .await?;
+
+        Ok(records)
+    }
+
+    /// Problems found by SQLite, empty if the database is fine.
+    pub async fn integrity_check(&self) -> Result<Vec<String>, sqlx::Error> {
+        let mut problems = sqlx::query_scalar::<_, String>("PRAGMA integrity_check")
+            .fetch_all(self.connection)
+            .await?;
+
+        problems.retain(|problem| problem != "ok");
+
+        Ok(problems)
+    }
+}
@@ -0,0 +1,166 @@
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, SqlitePool};
+use utoipa::ToSchema;
+
+/// Roles are ordered, each one allows everything the previous one does.
+#[derive(
+    Serialize, Deserialize, sqlx::Type, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Editor,
+    Owner,
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct Member {
+    pub user_id: i64,
+    pub name: String,
+    pub role: Role,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct Invite {
+    pub id: i64,
+    pub collection_id: i64,
+    pub token: String,
+    pub role: Role,
+    pub expires_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct CreateInvite {
+    pub role: Role,
+    /// Defaults to one week
+    pub expires_in_hours: Option<i64>,
+}
+
+pub struct MembersController<'a> {
+    connection: &'a SqlitePool,
+}
+
+impl<'a> MembersController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        MembersController { connection }
+    }
+
+    pub async fn get_for_collection(&self, collection_id: i64) -> Result<Vec<Member>, sqlx::Error> {
+        let records = sqlx::query_as::<_, Member>(
+            "SELECT m.user_id, u.name, m.role, m.created_at FROM collection_members as m JOIN users as u ON u.id = m.user_id WHERE m.collection_id = ? ORDER BY m.created_at, u.name",
+        )
+        .bind(collection_id)
+        .fetch_all(self.connection)
+        .await?;
+
+        Ok(records)
+    }
+
+    pub async fn get_role(
+        &self,
+        collection_id: i64,
+        user_id: i64,
+    ) -> Result<Option<Role>, sqlx::Error> {
+        let role = sqlx::query_scalar::<_, Role>(
+            "SELECT role FROM collection_members WHERE collection_id = ? AND user_id = ?",
+        )
+        .bind(collection_id)
+        .bind(user_id)
+        .fetch_optional(self.connection)
+        .await?;
+
+        Ok(role)
+    }
+
+    /// Ids of all collections `user_id` is a member of, in any role.
+    pub async fn collection_ids(&self, user_id: i64) -> Result<Vec<i64>, sqlx::Error> {
+        let ids = sqlx::query_scalar::<_, i64>(
+            "SELECT collection_id FROM collection_members WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_all(self.connection)
+        .await?;
+
+        Ok(ids)
+    }
+
+    pub async fn set_role(
+        &self,
+        collection_id: i64,
+        user_id: i64,
+        role: Role,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO collection_members (collection_id, user_id, role) VALUES (?, ?, ?) ON CONFLICT (collection_id, user_id) DO UPDATE SET role = excluded.role",
+        )
+        .bind(collection_id)
+        .bind(user_id)
+        .bind(role)
+        .execute(self.connection)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(&self, collection_id: i64, user_id: i64) -> Result<u64, sqlx::Error> {
+        let record =
+            sqlx::query("DELETE FROM collection_members WHERE collection_id = ? AND user_id = ?")
+                .bind(collection_id)
+                .bind(user_id)
+                .execute(self.connection)
+                .await?;
+
+        Ok(record.rows_affected())
+    }
+
+    pub async fn create_invite(
+        &self,
+        collection_id: i64,
+        created_by: i64,
+        invite: &CreateInvite,
+    ) -> Result<Invite, sqlx::Error> {
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+
+        let record = sqlx::query_as::<_, Invite>(
+            "INSERT INTO collection_invites (collection_id, token, role, created_by, expires_at) VALUES (?, ?, ?, ?, datetime('now', ?)) RETURNING id, collection_id, token, role, expires_at",
+        )
+        .bind(collection_id)
+        .bind(token)
+        .bind(invite.role)
+        .bind(created_by)
+        .bind(format!("+{} hours", invite.expires_in_hours.unwrap_or(24 * 7).max(1)))
+        .fetch_one(self.connection)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Joins the collection of an unexpired invite. Existing members keep
+    /// their role if it is higher than the invited one. Returns the
+    /// collection id, or `RowNotFound` for unknown or expired tokens and
+    /// collections in the trash.
+    pub async fn accept_invite(&self, token: &str, user_id: i64) -> Result<i64, sqlx::Error> {
+        let invite = sqlx::query_as::<_, Invite>(
+            "SELECT i.id, i.collection_id, i.token, i.role, i.expires_at FROM collection_invites as i JOIN collections as c ON c.id = i.collection_id WHERE i.token = ? AND i.expires_at > CURRENT_TIMESTAMP AND c.deleted_at IS NULL",
+        )
+        .bind(token)
+        .fetch_one(self.connection)
+        .await?;
+
+        let current = self.get_role(invite.collection_id, user_id).await?;
+
+        if current.is_none_or(|role| role < invite.role) {
+            self.set_role(invite.collection_id, user_id, invite.role)
+                .await?;
+        }
+
+        Ok(invite.collection_id)
+    }
+}
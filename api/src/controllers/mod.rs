@@ -1,3 +1,15 @@
+pub mod activity;
 pub mod collections;
 pub mod controller;
+pub mod dump;
+pub mod llm_calls;
+pub mod maintenance;
+pub mod members;
+pub mod prompt_templates;
+pub mod questions;
+pub mod reviews;
+pub mod revisions;
+pub mod tags;
+pub mod trash;
+pub mod users;
 pub mod words;
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{prelude::FromRow, types::Json, SqlitePool};
+use utoipa::ToSchema;
+
+use super::controller::Controller;
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct PromptTemplate {
+    pub id: i64,
+    pub name: String,
+    pub version: i64,
+    pub system_prompt: String,
+    pub user_prompt: String,
+    #[schema(value_type = Object)]
+    pub response_schema: Json<Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct CreatePromptTemplate {
+    pub name: String,
+    pub system_prompt: String,
+    pub user_prompt: String,
+    pub response_schema: Value,
+}
+
+pub struct PromptTemplatesController<'a> {
+    connection: &'a SqlitePool,
+    collection: &'a str,
+}
+
+impl<'a> PromptTemplatesController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        PromptTemplatesController {
+            connection,
+            collection: "prompt_templates",
+        }
+    }
+
+    pub async fn get_latest(&self, name: &str) -> Result<PromptTemplate, sqlx::Error> {
+        let record = sqlx::query_as::<_, PromptTemplate>(
+            "SELECT * FROM prompt_templates WHERE name = ? ORDER BY version DESC LIMIT 1",
+        )
+        .bind(name)
+        .fetch_one(self.get_connection())
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Templates are never edited in place, so questions keep pointing at the
+    /// exact prompt they were generated with. Saving a template adds the next
+    /// version under its name instead.
+    pub async fn create_version(&self, template: CreatePromptTemplate) -> Result<i64, sqlx::Error> {
+        let record = sqlx::query(
+            "INSERT INTO prompt_templates (name, version, system_prompt, user_prompt, response_schema) VALUES (?, (SELECT COALESCE(MAX(version), 0) + 1 FROM prompt_templates WHERE name = ?), ?, ?, ?)",
+        )
+        .bind(&template.name)
+        .bind(&template.name)
+        .bind(&template.system_prompt)
+        .bind(&template.user_prompt)
+        .bind(Json(&template.response_schema))
+        .execute(self.get_connection())
+        .await?;
+
+        Ok(record.last_insert_rowid())
+    }
+}
+
+impl Controller<PromptTemplate> for PromptTemplatesController<'_> {
+    fn get_collection(&self) -> &str {
+        self.collection
+    }
+
+    fn get_connection(&self) -> &SqlitePool {
+        self.connection
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, types::Json, SqlitePool};
+use utoipa::ToSchema;
+
+use super::controller::Controller;
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[schema(as = QuestionOption)]
+pub struct Option {
+    pub name: String,
+    pub correct: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Question {
+    pub question: String,
+    pub options: Vec<Option>,
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+pub struct GeneratedQuestion {
+    pub id: i64,
+    pub word_id: i64,
+    pub kind: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub question: Json<Question>,
+    pub served: i64,
+    pub template_id: std::option::Option<i64>,
+}
+
+pub struct QuestionsController<'a> {
+    connection: &'a SqlitePool,
+    collection: &'a str,
+}
+
+impl<'a> QuestionsController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        QuestionsController {
+            connection,
+            collection: "generated_questions",
+        }
+    }
+
+    pub async fn count_for_word(
+        &self,
+        word_id: i64,
+        kind: &str,
+        source_language: &str,
+        target_language: &str,
+    ) -> Result<i64, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM generated_questions WHERE word_id = ? AND kind = ? AND source_language = ? AND target_language = ?",
+        )
+        .bind(word_id)
+        .bind(kind)
+        .bind(source_language)
+        .bind(target_language)
+        .fetch_one(self.get_connection())
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn store(
+        &self,
+        word_id: i64,
+        kind: &str,
+        source_language: &str,
+        target_language: &str,
+        template_id: i64,
+        question: &Question,
+    ) -> Result<i64, sqlx::Error> {
+        let record = sqlx::query(
+            "INSERT INTO generated_questions (word_id, kind, source_language, target_language, template_id, question) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(word_id)
+        .bind(kind)
+        .bind(source_language)
+        .bind(target_language)
+        .bind(template_id)
+        .bind(Json(question))
+        .execute(self.get_connection())
+        .await?;
+
+        Ok(record.last_insert_rowid())
+    }
+
+    /// Picks the least served question of the pool and marks it as served, so
+    /// consecutive calls rotate through all variants of a word. Pools are kept
+    /// per language pair, as questions are written in the source language and
+    /// the same word is asked about differently in each written standard.
+    pub async fn next_for_word(
+        &self,
+        word_id: i64,
+        kind: &str,
+        source_language: &str,
+        target_language: &str,
+    ) -> Result<Question, sqlx::Error> {
+        let record = sqlx::query_as::<_, GeneratedQuestion>(
+            "UPDATE generated_questions SET served = served + 1, last_served_at = CURRENT_TIMESTAMP WHERE id = (SELECT id FROM generated_questions WHERE word_id = ? AND kind = ? AND source_language = ? AND target_language = ? ORDER BY served, last_served_at, id LIMIT 1) RETURNING id, word_id, kind, source_language, target_language, question, served, template_id",
+        )
+        .bind(word_id)
+        .bind(kind)
+        .bind(source_language)
+        .bind(target_language)
+        .fetch_one(self.get_connection())
+        .await?;
+
+        Ok(record.question.0)
+    }
+}
+
+impl Controller<GeneratedQuestion> for QuestionsController<'_> {
+    fn get_collection(&self) -> &str {
+        self.collection
+    }
+
+    fn get_connection(&self) -> &SqlitePool {
+        self.connection
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, SqlitePool};
+use utoipa::ToSchema;
+
+use super::controller::Controller;
+
+/// Highest Leitner box, words in it are asked every `2^(MAX_BOX - 1)` days.
+pub const MAX_BOX: i64 = 5;
+
+/// Reviews a word needs before its difficulty is derived from them.
+pub const MIN_REVIEWS_FOR_DIFFICULTY: i64 = 3;
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct Review {
+    pub id: i64,
+    pub word_id: i64,
+    pub user_id: Option<i64>,
+    pub correct: bool,
+    #[serde(rename = "box")]
+    pub r#box: i64,
+    pub due_at: String,
+    pub created_at: String,
+}
+
+pub struct ReviewsController<'a> {
+    connection: &'a SqlitePool,
+    collection: &'a str,
+}
+
+impl<'a> ReviewsController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        ReviewsController {
+            connection,
+            collection: "reviews",
+        }
+    }
+
+    /// Records an answer and schedules the next review. Correct answers move
+    /// the word up one box, wrong ones back to the first, and each box doubles
+    /// the days until the word is due again.
+    pub async fn record(
+        &self,
+        word_id: i64,
+        user_id: Option<i64>,
+        correct: bool,
+    ) -> Result<Review, sqlx::Error> {
+        let last_box: i64 = sqlx::query_scalar(
+            "SELECT box FROM reviews WHERE word_id = ?1 AND user_id IS ?2 ORDER BY id DESC LIMIT 1",
+        )
+        .bind(word_id)
+        .bind(user_id)
+        .fetch_optional(self.get_connection())
+        .await?
+        .unwrap_or(0);
+
+        let r#box = if correct {
+            (last_box + 1).min(MAX_BOX)
+        } else {
+            1
+        };
+
+        let record = sqlx::query_as::<_, Review>(
+            "INSERT INTO reviews (word_id, user_id, correct, box, due_at) VALUES (?, ?, ?, ?, datetime('now', '+' || ? || ' days')) RETURNING *",
+        )
+        .bind(word_id)
+        .bind(user_id)
+        .bind(correct)
+        .bind(r#box)
+        .bind(1 << (r#box - 1))
+        .fetch_one(self.get_connection())
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Sets the difficulty of every reviewed word from 1 (always answered
+    /// correctly) to 5 (never), returning how many words changed.
+    pub async fn recompute_difficulty(&self) -> Result<u64, sqlx::Error> {
+        let record = sqlx::query(
+            "UPDATE words SET difficulty = computed.difficulty FROM (SELECT word_id, COUNT(*) as total, 1 + CAST(ROUND(4.0 * SUM(NOT correct) / COUNT(*)) AS INTEGER) as difficulty FROM reviews GROUP BY word_id) as computed WHERE computed.word_id = words.id AND computed.total >= ? AND words.deleted_at IS NULL AND words.difficulty IS NOT computed.difficulty",
+        )
+        .bind(MIN_REVIEWS_FOR_DIFFICULTY)
+        .execute(self.get_connection())
+        .await?;
+
+        Ok(record.rows_affected())
+    }
+
+    pub async fn get_for_word(&self, word_id: i64) -> Result<Vec<Review>, sqlx::Error> {
+        let records =
+            sqlx::query_as::<_, Review>("SELECT * FROM reviews WHERE word_id = ? ORDER BY id")
+                .bind(word_id)
+                .fetch_all(self.get_connection())
+                .await?;
+
+        Ok(records)
+    }
+
+    /// Signed in users who answered within the last `minutes`. Anonymous
+    /// answers can't be told apart, so they are left out.
+    pub async fn count_active_learners(&self, minutes: i64) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT user_id) FROM reviews WHERE created_at >= datetime('now', '-' || ? || ' minutes')",
+        )
+        .bind(minutes)
+        .fetch_one(self.get_connection())
+        .await?;
+
+        Ok(count)
+    }
+}
+
+impl Controller<Review> for ReviewsController<'_> {
+    fn get_collection(&self) -> &str {
+        self.collection
+    }
+
+    fn get_connection(&self) -> &SqlitePool {
+        self.connection
+    }
+}
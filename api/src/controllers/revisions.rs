@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{prelude::FromRow, types::Json, SqlitePool};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
+pub struct Change {
+    pub from: Value,
+    pub to: Value,
+}
+
+pub type Changes = BTreeMap<String, Change>;
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct Revision {
+    pub id: i64,
+    pub word_id: i64,
+    pub user_id: Option<i64>,
+    pub user_name: Option<String>,
+    #[schema(value_type = BTreeMap<String, Change>)]
+    pub changes: Json<Changes>,
+    pub created_at: String,
+}
+
+/// Fields that change on every write and aren't part of the history.
+const UNTRACKED: &[&str] = &["id", "version", "updated_at"];
+
+/// Field-level difference between two serialized versions of a word.
+pub fn diff(before: &Value, after: &Value) -> Changes {
+    let (Some(before), Some(after)) = (before.as_object(), after.as_object()) else {
+        return Changes::new();
+    };
+
+    after
+        .iter()
+        .filter(|(key, _)| !UNTRACKED.contains(&key.as_str()))
+        .filter_map(|(key, to)| {
+            let from = before.get(key).cloned().unwrap_or(Value::Null);
+
+            (from != *to).then(|| {
+                (
+                    key.clone(),
+                    Change {
+                        from,
+                        to: to.clone(),
+                    },
+                )
+            })
+        })
+        .collect()
+}
+
+pub struct RevisionsController<'a> {
+    connection: &'a SqlitePool,
+}
+
+impl<'a> RevisionsController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        RevisionsController { connection }
+    }
+
+    pub async fn record(
+        &self,
+        word_id: i64,
+        user_id: Option<i64>,
+        changes: &Changes,
+    ) -> Result<i64, sqlx::Error> {
+        let record =
+            sqlx::query("INSERT INTO word_revisions (word_id, user_id, changes) VALUES (?, ?, ?)")
+                .bind(word_id)
+                .bind(user_id)
+                .bind(Json(changes))
+                .execute(self.connection)
+                .await?;
+
+        Ok(record.last_insert_rowid())
+    }
+
+    /// Newest revision first.
+    pub async fn get_for_word(&self, word_id: i64) -> Result<Vec<Revision>, sqlx::Error> {
+        let records = sqlx::query_as::<_, Revision>(
+            "SELECT r.id, r.word_id, r.user_id, u.name as user_name, r.changes, r.created_at FROM word_revisions as r LEFT JOIN users as u ON u.id = r.user_id WHERE r.word_id = ? ORDER BY r.id DESC",
+        )
+        .bind(word_id)
+        .fetch_all(self.connection)
+        .await?;
+
+        Ok(records)
+    }
+
+    pub async fn get_one(&self, word_id: i64, id: i64) -> Result<Revision, sqlx::Error> {
+        let record = sqlx::query_as::<_, Revision>(
+            "SELECT r.id, r.word_id, r.user_id, u.name as user_name, r.changes, r.created_at FROM word_revisions as r LEFT JOIN users as u ON u.id = r.user_id WHERE r.word_id = ? AND r.id = ?",
+        )
+        .bind(word_id)
+        .bind(id)
+        .fetch_one(self.connection)
+        .await?;
+
+        Ok(record)
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{prelude::FromRow, SqlitePool};
+use utoipa::ToSchema;
+
+use super::controller::{columns, insert_row, Controller};
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+}
+
+pub struct TagsController<'a> {
+    connection: &'a SqlitePool,
+    collection: &'a str,
+}
+
+impl<'a> TagsController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        TagsController {
+            connection,
+            collection: "tags",
+        }
+    }
+
+    pub async fn get_for_word(&self, word_id: i64) -> Result<Vec<Tag>, sqlx::Error> {
+        let records = sqlx::query_as::<_, Tag>(
+            "SELECT t.* FROM word_tags JOIN tags as t ON word_tags.tag_id = t.id WHERE word_tags.word_id = ? AND t.deleted_at IS NULL ORDER BY t.name",
+        )
+        .bind(word_id)
+        .fetch_all(self.get_connection())
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Replaces the tags of a word, creating tags that don't exist yet.
+    pub async fn set_for_word(&self, word_id: i64, names: &[String]) -> Result<(), sqlx::Error> {
+        let mut transaction = self.get_connection().begin().await?;
+
+        sqlx::query("DELETE FROM word_tags WHERE word_id = ?")
+            .bind(word_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        for name in names {
+            // Tagging with a name that is in the trash restores that tag
+            sqlx::query(
+                "INSERT INTO tags (name) VALUES (?) ON CONFLICT (name) DO UPDATE SET deleted_at = NULL",
+            )
+                .bind(name)
+                .execute(&mut *transaction)
+                .await?;
+
+            sqlx::query(
+                "INSERT OR IGNORE INTO word_tags (word_id, tag_id) SELECT ?, id FROM tags WHERE name = ?",
+            )
+            .bind(word_id)
+            .bind(name)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await
+    }
+}
+
+impl Controller<Tag> for TagsController<'_> {
+    fn get_collection(&self) -> &str {
+        self.collection
+    }
+
+    fn get_connection(&self) -> &SqlitePool {
+        self.connection
+    }
+
+    fn soft_deletes(&self) -> bool {
+        true
+    }
+
+    /// Creating a tag with the name of one in the trash restores that tag,
+    /// like tagging a word with it does. Names of live tags stay taken.
+    #[tracing::instrument(level = "debug", skip_all, fields(table = "tags"))]
+    async fn create(&self, item: Value) -> Result<i64, sqlx::Error> {
+        let columns = columns(self.connection, "tags", &item, &[]).await?;
+
+        if let Some(name) = item.get("name").and_then(Value::as_str) {
+            let restored = sqlx::query_scalar(
+                "UPDATE tags SET deleted_at = NULL WHERE name = ? AND deleted_at IS NOT NULL RETURNING id",
+            )
+            .bind(name)
+            .fetch_optional(self.connection)
+            .await?;
+
+            if let Some(id) = restored {
+                return Ok(id);
+            }
+        }
+
+        insert_row(self.connection, "tags", &columns).await
+    }
+}
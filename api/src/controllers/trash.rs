@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, SqlitePool};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Words,
+    Collections,
+    Tags,
+}
+
+impl TrashKind {
+    fn table(self) -> &'static str {
+        match self {
+            TrashKind::Words => "words",
+            TrashKind::Collections => "collections",
+            TrashKind::Tags => "tags",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct TrashItem {
+    pub kind: String,
+    pub id: i64,
+    pub name: String,
+    pub deleted_at: String,
+}
+
+/// Collections only show up in the trash of their owners, words and tags are
+/// shared by everyone.
+const COLLECTION_OWNER: &str = "(owner_id IS NULL OR EXISTS (SELECT 1 FROM collection_members as m WHERE m.collection_id = collections.id AND m.user_id = ?1 AND m.role = 'owner'))";
+
+pub struct TrashController<'a> {
+    connection: &'a SqlitePool,
+}
+
+impl<'a> TrashController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        TrashController { connection }
+    }
+
+    /// Most recently deleted first.
+    pub async fn get_all(&self, user_id: Option<i64>) -> Result<Vec<TrashItem>, sqlx::Error> {
+        let records = sqlx::query_as::<_, TrashItem>(&format!(
+            "SELECT 'words' as kind, id, word as name, deleted_at FROM words WHERE deleted_at IS NOT NULL UNION ALL SELECT 'tags', id, name, deleted_at FROM tags WHERE deleted_at IS NOT NULL UNION ALL SELECT 'collections', id, name, deleted_at FROM collections WHERE deleted_at IS NOT NULL AND {} ORDER BY deleted_at DESC, id DESC",
+            COLLECTION_OWNER
+        ))
+        .bind(user_id)
+        .fetch_all(self.connection)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Returns `RowNotFound` if the item is not in the caller's trash.
+    pub async fn restore(
+        &self,
+        kind: TrashKind,
+        id: i64,
+        user_id: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        let owner = match kind {
+            TrashKind::Collections => format!(" AND {}", COLLECTION_OWNER),
+            _ => String::new(),
+        };
+
+        let record = sqlx::query(&format!(
+            "UPDATE {} SET deleted_at = NULL WHERE id = ?2 AND deleted_at IS NOT NULL{}",
+            kind.table(),
+            owner
+        ))
+        .bind(user_id)
+        .bind(id)
+        .execute(self.connection)
+        .await?;
+
+        if record.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Permanently removes everything deleted more than `retention_days` ago,
+    /// returning the number of removed items.
+    pub async fn purge(&self, retention_days: i64) -> Result<u64, sqlx::Error> {
+        let cutoff = format!("-{} days", retention_days);
+        let expired = "deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?)";
+
+        let mut transaction = self.connection.begin().await?;
+
+        // Word lists don't cascade, every other reference does
+        sqlx::query(&format!(
+            "DELETE FROM collection_words WHERE collection_id IN (SELECT id FROM collections WHERE {0}) OR word_id IN (SELECT id FROM words WHERE {0})",
+            expired
+        ))
+        .bind(&cutoff)
+        .bind(&cutoff)
+        .execute(&mut *transaction)
+        .await?;
+
+        let mut purged = 0;
+
+        for kind in [TrashKind::Collections, TrashKind::Words, TrashKind::Tags] {
+            purged += sqlx::query(&format!("DELETE FROM {} WHERE {}", kind.table(), expired))
+                .bind(&cutoff)
+                .execute(&mut *transaction)
+                .await?
+                .rows_affected();
+        }
+
+        transaction.commit().await?;
+
+        Ok(purged)
+    }
+}
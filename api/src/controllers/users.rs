@@ -0,0 +1,148 @@
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, SqlitePool};
+use utoipa::ToSchema;
+
+use super::controller::Controller;
+
+/// How long a session token stays valid after logging in.
+pub const SESSION_DAYS: i64 = 30;
+
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    #[serde(skip)]
+    pub password_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Credentials {
+    pub name: String,
+    pub password: String,
+}
+
+pub struct UsersController<'a> {
+    connection: &'a SqlitePool,
+    collection: &'a str,
+}
+
+impl<'a> UsersController<'a> {
+    pub fn new(connection: &'a SqlitePool) -> Self {
+        UsersController {
+            connection,
+            collection: "users",
+        }
+    }
+
+    pub async fn register(&self, credentials: &Credentials) -> Result<i64, sqlx::Error> {
+        let password_hash = hash(&credentials.password).await?;
+
+        let record = sqlx::query("INSERT INTO users (name, password_hash) VALUES (?, ?)")
+            .bind(&credentials.name)
+            .bind(password_hash)
+            .execute(self.get_connection())
+            .await?;
+
+        Ok(record.last_insert_rowid())
+    }
+
+    /// Replaces the password and ends all sessions of the user, returning
+    /// how many users were changed.
+    pub async fn set_password(&self, credentials: &Credentials) -> Result<u64, sqlx::Error> {
+        let password_hash = hash(&credentials.password).await?;
+
+        let mut transaction = self.connection.begin().await?;
+
+        let record = sqlx::query("UPDATE users SET password_hash = ? WHERE name = ?")
+            .bind(password_hash)
+            .bind(&credentials.name)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query("DELETE FROM sessions WHERE user_id IN (SELECT id FROM users WHERE name = ?)")
+            .bind(&credentials.name)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(record.rows_affected())
+    }
+
+    /// Checks the credentials and opens a new session, returning its token.
+    /// Wrong names and wrong passwords both end in `RowNotFound`.
+    pub async fn login(&self, credentials: &Credentials) -> Result<String, sqlx::Error> {
+        let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE name = ?")
+            .bind(&credentials.name)
+            .fetch_one(self.get_connection())
+            .await?;
+
+        if !verify(&credentials.password, &user.password_hash).await? {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(48)
+            .map(char::from)
+            .collect();
+
+        sqlx::query("DELETE FROM sessions WHERE expires_at <= CURRENT_TIMESTAMP")
+            .execute(self.get_connection())
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO sessions (token, user_id, expires_at) VALUES (?, ?, datetime('now', ?))",
+        )
+        .bind(&token)
+        .bind(user.id)
+        .bind(format!("+{} days", SESSION_DAYS))
+        .execute(self.get_connection())
+        .await?;
+
+        Ok(token)
+    }
+
+    pub async fn get_by_token(&self, token: &str) -> Result<User, sqlx::Error> {
+        let record = sqlx::query_as::<_, User>(
+            "SELECT u.* FROM users as u JOIN sessions ON sessions.user_id = u.id WHERE sessions.token = ? AND sessions.expires_at > CURRENT_TIMESTAMP",
+        )
+        .bind(token)
+        .fetch_one(self.get_connection())
+        .await?;
+
+        Ok(record)
+    }
+}
+
+/// bcrypt is slow on purpose, so it runs on the blocking pool instead of
+/// holding up other requests.
+async fn hash(password: &str) -> Result<String, sqlx::Error> {
+    let password = password.to_string();
+
+    tokio::task::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST))
+        .await
+        .map_err(|err| sqlx::Error::Encode(Box::new(err)))?
+        .map_err(|err| sqlx::Error::Encode(Box::new(err)))
+}
+
+/// Checks a password on the blocking pool. A malformed stored hash counts as
+/// a wrong password.
+async fn verify(password: &str, password_hash: &str) -> Result<bool, sqlx::Error> {
+    let (password, password_hash) = (password.to_string(), password_hash.to_string());
+
+    tokio::task::spawn_blocking(move || bcrypt::verify(password, &password_hash).unwrap_or(false))
+        .await
+        .map_err(|err| sqlx::Error::Encode(Box::new(err)))
+}
+
+impl Controller<User> for UsersController<'_> {
+    fn get_collection(&self) -> &str {
+        self.collection
+    }
+
+    fn get_connection(&self) -> &SqlitePool {
+        self.connection
+    }
+}
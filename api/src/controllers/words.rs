@@ -1,17 +1,81 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
-use sqlx::{prelude::FromRow, SqlitePool};
+use serde_json::Value;
+use sqlx::{prelude::FromRow, QueryBuilder, Sqlite, SqlitePool};
+use utoipa::ToSchema;
 
-use crate::with_keys;
+use crate::languages::{
+    self,
+    grading::{self, Direction, Grade},
+    DEFAULT_SOURCE_LANGUAGE, DEFAULT_TARGET_LANGUAGE,
+};
 
-use super::controller::Controller;
+use super::{
+    collections::CollectionsController,
+    controller::{columns, delete_row, update_row, Controller},
+    reviews::ReviewsController,
+    revisions::{self, RevisionsController},
+};
 
-#[derive(Serialize, Deserialize, FromRow, Clone, Debug)]
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
 pub struct Word {
     pub id: i64,
     pub word: String,
     pub translation: String,
     pub image: String,
     pub audio: String,
+    pub source_language: Option<String>,
+    pub target_language: Option<String>,
+    pub part_of_speech: Option<String>,
+    pub difficulty: Option<i64>,
+    /// Bumped by the database on every update
+    pub version: i64,
+    pub updated_at: Option<String>,
+}
+
+/// Saved filter of a smart collection, also used for `/api/words?tag=`. All
+/// given conditions have to match.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, ToSchema)]
+pub struct WordFilter {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub part_of_speech: Option<String>,
+    /// Only words that are (or aren't) due for review. Words never reviewed
+    /// count as due.
+    pub due: Option<bool>,
+    pub min_difficulty: Option<i64>,
+    pub max_difficulty: Option<i64>,
+}
+
+/// How a word is written in one written standard of its language, e.g. the
+/// Nynorsk (`nn`) form of a Bokmål (`nb`) word.
+#[derive(Serialize, Deserialize, FromRow, Clone, Debug, ToSchema)]
+pub struct Spelling {
+    pub language: String,
+    pub spelling: String,
+}
+
+impl Word {
+    /// The word as written in `language`, falling back to `word` when there is
+    /// no spelling for that standard.
+    pub fn spelling_for(&self, spellings: &[Spelling], language: &str) -> String {
+        spellings
+            .iter()
+            .find(|spelling| spelling.language == language)
+            .map(|spelling| spelling.spelling.clone())
+            .unwrap_or(self.word.clone())
+    }
+}
+
+/// Words that are the same after normalization. `exact` is set when they
+/// also match character for character.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct DuplicateGroup {
+    pub word: String,
+    pub translation: String,
+    pub exact: bool,
+    pub words: Vec<Word>,
 }
 
 // with_keys! {
@@ -36,12 +100,314 @@ impl<'a> WordsController<'a> {
         }
     }
 
-    pub async fn get_random_word(&self, id: i64) -> Result<Word, sqlx::Error> {
-        let record = sqlx::query_as::<_, Word>("SELECT w.id, w.word, w.translation, w.image, w.audio FROM collections as c LEFT JOIN collection_words ON c.id = collection_words.collection_id LEFT JOIN words as w ON collection_words.word_id = w.id WHERE c.id == ? ORDER BY RANDOM() LIMIT 1").bind(id)
-        .fetch_one(self.get_connection())
+    /// Updates a word and records what changed as a revision by `user_id`.
+    /// Updates that change nothing leave no revision behind. Given a version,
+    /// nothing happens unless the word is still at it, which shows as no word
+    /// changed in the returned count.
+    pub async fn update_as(
+        &self,
+        id: i64,
+        user_id: Option<i64>,
+        version: Option<i64>,
+        item: Value,
+    ) -> Result<u64, sqlx::Error> {
+        let before = self.get_one(id).await?;
+
+        let columns = columns(self.connection, self.collection, &item, &[]).await?;
+        let changed = update_row(
+            self.connection,
+            self.collection,
+            id,
+            &columns,
+            true,
+            version,
+        )
         .await?;
 
-        Ok(record)
+        if version.is_some() && changed == 0 {
+            return Ok(0);
+        }
+
+        let after = self.get_one(id).await?;
+
+        let changes = revisions::diff(
+            &serde_json::to_value(before).unwrap(),
+            &serde_json::to_value(after).unwrap(),
+        );
+
+        if !changes.is_empty() {
+            RevisionsController::new(self.connection)
+                .record(id, user_id, &changes)
+                .await?;
+        }
+
+        Ok(changed)
+    }
+
+    /// Moves a word to the trash, given a version only if it is still at it.
+    /// Returns how many words were moved.
+    pub async fn delete_at(&self, id: i64, version: Option<i64>) -> Result<u64, sqlx::Error> {
+        delete_row(self.connection, self.collection, id, true, version).await
+    }
+
+    /// Grades an answer to a word, practiced in `collection_id` if given, and
+    /// records the review for `user_id`.
+    pub async fn answer(
+        &self,
+        id: i64,
+        user_id: Option<i64>,
+        collection_id: Option<i64>,
+        direction: Direction,
+        answer: &str,
+    ) -> Result<Grade, sqlx::Error> {
+        let word = self.get_one(id).await?;
+        let spellings = self.get_spellings(id).await?;
+
+        let collection = match collection_id {
+            Some(collection_id) => Some(
+                CollectionsController::new(self.connection)
+                    .get_info(collection_id)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let grade = grading::grade(&word, &spellings, collection.as_ref(), direction, answer);
+
+        ReviewsController::new(self.connection)
+            .record(id, user_id, grade.correct)
+            .await?;
+
+        Ok(grade)
+    }
+
+    /// Undoes a revision by writing its old values back through
+    /// `update_as`, which records the revert as a revision of its own.
+    pub async fn revert(
+        &self,
+        id: i64,
+        revision_id: i64,
+        user_id: Option<i64>,
+        version: Option<i64>,
+    ) -> Result<u64, sqlx::Error> {
+        let revision = RevisionsController::new(self.connection)
+            .get_one(id, revision_id)
+            .await?;
+
+        let item = revision
+            .changes
+            .0
+            .into_iter()
+            .map(|(key, change)| (key, change.from))
+            .collect();
+
+        self.update_as(id, user_id, version, Value::Object(item))
+            .await
+    }
+
+    pub async fn get_filtered(&self, filter: &WordFilter) -> Result<Vec<Word>, sqlx::Error> {
+        self.get_page(filter, None, 0).await
+    }
+
+    /// Words matching the filter in id order, `limit` at a time. Without a
+    /// limit all of them are returned.
+    #[tracing::instrument(level = "debug", skip_all, fields(table = "words", limit = limit, offset = offset))]
+    pub async fn get_page(
+        &self,
+        filter: &WordFilter,
+        limit: Option<i64>,
+        offset: i64,
+    ) -> Result<Vec<Word>, sqlx::Error> {
+        let mut query =
+            QueryBuilder::<Sqlite>::new("SELECT w.* FROM words as w WHERE w.deleted_at IS NULL");
+
+        for tag in &filter.tags {
+            query
+                .push(" AND EXISTS (SELECT 1 FROM word_tags JOIN tags ON tags.id = word_tags.tag_id WHERE word_tags.word_id = w.id AND tags.deleted_at IS NULL AND tags.name = ")
+                .push_bind(tag.clone())
+                .push(")");
+        }
+
+        if let Some(part_of_speech) = &filter.part_of_speech {
+            query
+                .push(" AND w.part_of_speech = ")
+                .push_bind(part_of_speech.clone());
+        }
+
+        if let Some(min_difficulty) = filter.min_difficulty {
+            query
+                .push(" AND w.difficulty >= ")
+                .push_bind(min_difficulty);
+        }
+
+        if let Some(max_difficulty) = filter.max_difficulty {
+            query
+                .push(" AND w.difficulty <= ")
+                .push_bind(max_difficulty);
+        }
+
+        if let Some(due) = filter.due {
+            query.push(if due { " AND " } else { " AND NOT " }).push(
+                "COALESCE((SELECT due_at FROM reviews WHERE reviews.word_id = w.id ORDER BY reviews.id DESC LIMIT 1) <= datetime('now'), TRUE)",
+            );
+        }
+
+        query.push(" ORDER BY w.id");
+
+        if let Some(limit) = limit {
+            query
+                .push(" LIMIT ")
+                .push_bind(limit)
+                .push(" OFFSET ")
+                .push_bind(offset);
+        }
+
+        let records = query
+            .build_query_as::<Word>()
+            .fetch_all(self.get_connection())
+            .await?;
+
+        Ok(records)
+    }
+
+    pub async fn get_spellings(&self, id: i64) -> Result<Vec<Spelling>, sqlx::Error> {
+        let records = sqlx::query_as::<_, Spelling>(
+            "SELECT language, spelling FROM word_spellings WHERE word_id = ? ORDER BY language",
+        )
+        .bind(id)
+        .fetch_all(self.get_connection())
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Replaces all spellings of a word with the given ones.
+    pub async fn set_spellings(&self, id: i64, spellings: &[Spelling]) -> Result<(), sqlx::Error> {
+        let mut transaction = self.get_connection().begin().await?;
+
+        sqlx::query("DELETE FROM word_spellings WHERE word_id = ?")
+            .bind(id)
+            .execute(&mut *transaction)
+            .await?;
+
+        for spelling in spellings {
+            sqlx::query(
+                "INSERT INTO word_spellings (word_id, language, spelling) VALUES (?, ?, ?)",
+            )
+            .bind(id)
+            .bind(&spelling.language)
+            .bind(&spelling.spelling)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await
+    }
+
+    /// Groups words with the same word and translation, compared with the
+    /// normalization rules of their languages (case, whitespace, articles).
+    pub async fn get_duplicates(&self) -> Result<Vec<DuplicateGroup>, sqlx::Error> {
+        let words = self.get_all().await?;
+
+        let mut groups: BTreeMap<(String, String, String), Vec<Word>> = BTreeMap::new();
+
+        for word in words {
+            let target = word
+                .target_language
+                .clone()
+                .unwrap_or(DEFAULT_TARGET_LANGUAGE.to_string());
+            let source = word
+                .source_language
+                .as_deref()
+                .unwrap_or(DEFAULT_SOURCE_LANGUAGE);
+
+            let key = (
+                languages::rules(&target).normalize(&word.word),
+                languages::rules(source).normalize(&word.translation),
+                target,
+            );
+
+            groups.entry(key).or_default().push(word);
+        }
+
+        let duplicates = groups
+            .into_iter()
+            .filter(|(_, words)| words.len() > 1)
+            .map(|((word, translation, _), words)| DuplicateGroup {
+                word,
+                translation,
+                exact: words
+                    .iter()
+                    .all(|w| w.word == words[0].word && w.translation == words[0].translation),
+                words,
+            })
+            .collect();
+
+        Ok(duplicates)
+    }
+
+    /// Folds the duplicates into the surviving word `id` and deletes them, all
+    /// in one transaction. Collection memberships, spellings, tags, reviews and
+    /// generated questions move over to the survivor where it doesn't have them
+    /// already.
+    pub async fn merge(&self, id: i64, duplicate_ids: &[i64]) -> Result<(), sqlx::Error> {
+        let mut transaction = self.get_connection().begin().await?;
+
+        // Fails with RowNotFound before touching anything if the survivor is gone
+        sqlx::query("SELECT id FROM words WHERE id = ? AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_one(&mut *transaction)
+            .await?;
+
+        for duplicate_id in duplicate_ids
+            .iter()
+            .filter(|duplicate_id| **duplicate_id != id)
+        {
+            sqlx::query("UPDATE collection_words SET word_id = ?1 WHERE word_id = ?2 AND collection_id NOT IN (SELECT collection_id FROM collection_words WHERE word_id = ?1)")
+                .bind(id)
+                .bind(duplicate_id)
+                .execute(&mut *transaction)
+                .await?;
+
+            sqlx::query("UPDATE word_spellings SET word_id = ?1 WHERE word_id = ?2 AND language NOT IN (SELECT language FROM word_spellings WHERE word_id = ?1)")
+                .bind(id)
+                .bind(duplicate_id)
+                .execute(&mut *transaction)
+                .await?;
+
+            sqlx::query("UPDATE generated_questions SET word_id = ? WHERE word_id = ?")
+                .bind(id)
+                .bind(duplicate_id)
+                .execute(&mut *transaction)
+                .await?;
+
+            sqlx::query("UPDATE reviews SET word_id = ? WHERE word_id = ?")
+                .bind(id)
+                .bind(duplicate_id)
+                .execute(&mut *transaction)
+                .await?;
+
+            sqlx::query(
+                "INSERT OR IGNORE INTO word_tags (word_id, tag_id) SELECT ?, tag_id FROM word_tags WHERE word_id = ?",
+            )
+            .bind(id)
+            .bind(duplicate_id)
+            .execute(&mut *transaction)
+            .await?;
+
+            // Whatever is left over was already covered by the survivor
+            sqlx::query("DELETE FROM collection_words WHERE word_id = ?")
+                .bind(duplicate_id)
+                .execute(&mut *transaction)
+                .await?;
+
+            sqlx::query("DELETE FROM words WHERE id = ?")
+                .bind(duplicate_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        transaction.commit().await
     }
 }
 
@@ -53,4 +419,8 @@ impl Controller<Word> for WordsController<'_> {
     fn get_connection(&self) -> &SqlitePool {
         self.connection
     }
+
+    fn soft_deletes(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+};
+
+use crate::{
+    controllers::users::{User, UsersController},
+    endpoints::error::ApiError,
+    AppState,
+};
+
+/// The user behind the `Authorization: Bearer <token>` header. Use
+/// `Option<AuthUser>` for routes that also work anonymously.
+pub struct AuthUser(pub User);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let unauthorized = || {
+            ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "invalid or missing bearer token",
+            )
+        };
+
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(unauthorized)?;
+
+        let user = UsersController::new(&state.pool)
+            .get_by_token(token)
+            .await
+            .map_err(|_| unauthorized())?;
+
+        Ok(AuthUser(user))
+    }
+}
+
+/// An authenticated user listed in `ADMIN_USERS`, required for routes that
+/// expose or replace data of all users.
+pub struct AdminUser;
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AdminUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let AuthUser(user) = AuthUser::from_request_parts(parts, state).await?;
+
+        if !state.admin_users.contains(&user.name) {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "only administrators can do this",
+            ));
+        }
+
+        Ok(AdminUser)
+    }
+}
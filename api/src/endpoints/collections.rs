@@ -1,73 +1,249 @@
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::Response,
     Json,
 };
 use serde_json::Value;
 
 use crate::{
     controllers::{
-        collections::{Collection, CollectionsController},
+        collections::{
+            CloneOptions, Collection, CollectionsController, DiscoverEntry, DiscoverParams,
+        },
         controller::Controller,
     },
+    jobs::pregenerate,
     AppState,
 };
 
-pub async fn get_all_collections(State(state): State<Arc<AppState>>) -> Json<Vec<Collection>> {
+use super::{
+    auth::AuthUser,
+    error::{ApiError, ErrorBody},
+    etag,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/collections",
+    tag = "collections",
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Vec<Collection>)
+    )
+)]
+pub async fn get_all_collections(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+) -> Result<Json<Vec<Collection>>, ApiError> {
     let controller = CollectionsController::new(&state.pool);
 
-    let words = controller.get_all().await.unwrap();
+    let collections = controller
+        .get_all_for(user.map(|AuthUser(user)| user.id))
+        .await?;
 
-    Json(words)
+    Ok(Json(collections))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}",
+    tag = "collections",
+    params(("id" = i64, Path)),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Collection, headers(("etag" = String))),
+        (status = 304, description = "Matches If-None-Match"),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
 pub async fn get_one_collection(
     State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
     Path(id): Path<i64>,
-) -> Json<Collection> {
-    let controller = CollectionsController::new(&state.pool);
-
-    let word = controller.get_one(id).await.unwrap();
-
-    Json(word)
+) -> Result<Response, ApiError> {
+    let controller = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let collection = controller.get_one(id).await?;
+
+    // Private collections of others are reported as missing, not forbidden
+    if controller
+        .role_of(&collection, user.map(|AuthUser(user)| user.id))
+        .await?
+        .is_none()
+    {
+        return Err(sqlx::Error::RowNotFound.into());
+    }
+
+    Ok(etag::tagged(
+        &headers,
+        etag::collection_etag(&collection),
+        collection,
+    ))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/collections",
+    tag = "collections",
+    request_body = Object,
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Collection),
+        (status = 400, description = "Sets a column the server maintains", body = ErrorBody),
+        (status = 422, description = "A column is unknown, missing or violates a constraint", body = ErrorBody)
+    )
+)]
 pub async fn post_collection(
     State(state): State<Arc<AppState>>,
-    Json(mut word): Json<Value>,
-) -> Json<Collection> {
+    user: Option<AuthUser>,
+    Json(word): Json<Value>,
+) -> Result<Json<Collection>, ApiError> {
     let controller = CollectionsController::new(&state.pool);
 
-    let id = controller.create(word).await.unwrap();
+    let id = controller
+        .create_as(user.map(|AuthUser(user)| user.id), word)
+        .await?;
+
+    let collection = controller.get_one(id).await?;
 
-    let word = controller.get_one(id).await.unwrap();
+    pregenerate::spawn(state.clone(), collection.clone(), word_ids(&collection));
 
-    Json(word)
+    Ok(Json(collection))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/collections/{id}",
+    tag = "collections",
+    params(("id" = i64, Path)),
+    request_body = Object,
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Collection, headers(("etag" = String))),
+        (status = 400, description = "Sets a column the server maintains", body = ErrorBody),
+        (status = 403, description = "Role too low", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 412, description = "Changed since the given ETag", body = ErrorBody),
+        (status = 422, description = "A column is unknown, missing or violates a constraint", body = ErrorBody)
+    )
+)]
 pub async fn put_collection(
     State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
     Path(id): Path<i64>,
-    Json(mut word): Json<Value>,
-) -> Json<Collection> {
-    let controller = CollectionsController::new(&state.pool);
+    Json(word): Json<Value>,
+) -> Result<Response, ApiError> {
+    let controller = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let collection = controller.get_one(id).await?;
+    let version = etag::check_if_match(&headers, &etag::collection_etag(&collection))?
+        .then_some(collection.version);
 
-    controller.update(id, word).await.unwrap();
+    controller
+        .update_as(id, user.map(|AuthUser(user)| user.id), version, word)
+        .await?;
 
-    let word = controller.get_one(id).await.unwrap();
+    let collection = controller.get_one(id).await?;
 
-    Json(word)
+    pregenerate::spawn(state.clone(), collection.clone(), word_ids(&collection));
+
+    Ok(etag::tagged(
+        &HeaderMap::new(),
+        etag::collection_etag(&collection),
+        collection,
+    ))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/collections/{id}",
+    tag = "collections",
+    params(("id" = i64, Path)),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "Moved to the trash", body = ()),
+        (status = 403, description = "Role too low", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 412, description = "Changed since the given ETag", body = ErrorBody)
+    )
+)]
 pub async fn delete_collection(
     State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
     Path(id): Path<i64>,
-) -> Result<Json<()>, StatusCode> {
-    let controller = CollectionsController::new(&state.pool);
+) -> Result<Json<()>, ApiError> {
+    let controller = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
 
-    controller.delete(id).await.unwrap();
+    let collection = controller.get_one(id).await?;
+    let version = etag::check_if_match(&headers, &etag::collection_etag(&collection))?
+        .then_some(collection.version);
+
+    controller
+        .delete_as(id, user.map(|AuthUser(user)| user.id), version)
+        .await?;
 
     Ok(Json(()))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/discover",
+    tag = "collections",
+    params(DiscoverParams),
+    responses(
+        (status = 200, description = "OK", body = Vec<DiscoverEntry>)
+    )
+)]
+pub async fn discover_collections(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<DiscoverParams>,
+) -> Result<Json<Vec<DiscoverEntry>>, ApiError> {
+    let controller = CollectionsController::new(&state.pool);
+
+    Ok(Json(controller.discover(&params).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/collections/{id}/clone",
+    tag = "collections",
+    params(("id" = i64, Path)),
+    request_body = CloneOptions,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The clone", body = Collection),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn clone_collection(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<i64>,
+    options: Option<Json<CloneOptions>>,
+) -> Result<Json<Collection>, ApiError> {
+    let controller = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let source = controller.get_info(id).await?;
+
+    if controller.role_of(&source, Some(user.id)).await?.is_none() {
+        return Err(sqlx::Error::RowNotFound.into());
+    }
+
+    let options = options.map(|Json(options)| options).unwrap_or_default();
+    let clone_id = controller.clone_collection(id, user.id, &options).await?;
+
+    let collection = controller.get_one(clone_id).await?;
+
+    Ok(Json(collection))
+}
+
+fn word_ids(collection: &Collection) -> Vec<i64> {
+    collection.words.iter().map(|word| word.id).collect()
+}
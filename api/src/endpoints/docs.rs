@@ -0,0 +1,99 @@
+use axum::{response::Html, Json};
+use utoipa::{
+    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::controllers::{dump::ImportMode, trash::TrashKind};
+
+use super::{
+    collections, dump, health, llm_calls, maintenance, members, metrics, prompt_templates, tags,
+    trash, users, words,
+};
+
+/// The OpenAPI document, assembled from the `#[utoipa::path]` attributes on
+/// the handlers. Schemas they reference are collected automatically.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Drue", description = "Vocabulary practice API"),
+    paths(
+        words::get_all_words,
+        words::get_one_word,
+        words::post_word,
+        words::put_word,
+        words::delete_word,
+        words::get_duplicate_words,
+        words::post_merge,
+        words::post_answer,
+        words::get_word_tags,
+        words::put_word_tags,
+        words::get_word_reviews,
+        words::get_word_revisions,
+        words::revert_word,
+        words::get_spellings,
+        words::put_spellings,
+        words::generate_question,
+        collections::get_all_collections,
+        collections::get_one_collection,
+        collections::post_collection,
+        collections::put_collection,
+        collections::delete_collection,
+        collections::discover_collections,
+        collections::clone_collection,
+        members::get_members,
+        members::put_member,
+        members::delete_member,
+        members::post_invite,
+        members::accept_invite,
+        members::get_activity,
+        tags::get_all_tags,
+        tags::get_one_tag,
+        tags::post_tag,
+        tags::put_tag,
+        tags::delete_tag,
+        trash::get_trash,
+        trash::restore_from_trash,
+        users::post_user,
+        users::post_session,
+        users::get_me,
+        dump::get_export,
+        dump::post_import,
+        maintenance::get_integrity,
+        maintenance::post_checkpoint,
+        maintenance::post_backup,
+        llm_calls::get_llm_stats,
+        prompt_templates::get_all_prompt_templates,
+        prompt_templates::get_one_prompt_template,
+        prompt_templates::post_prompt_template,
+        prompt_templates::preview_prompt_template,
+        metrics::get_metrics,
+        health::get_healthz,
+        health::get_readyz,
+    ),
+    // Only referenced from parameters, which are not collected
+    components(schemas(TrashKind, ImportMode)),
+    modifiers(&BearerAuth)
+)]
+pub struct ApiDoc;
+
+/// Declares the session token scheme the `security` attributes refer to
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+            );
+        }
+    }
+}
+
+pub async fn get_openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+pub async fn get_docs() -> Html<&'static str> {
+    Html(include_str!("docs.html"))
+}
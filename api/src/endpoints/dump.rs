@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    controllers::dump::{Dump, DumpController, ImportMode, ImportSummary},
+    AppState,
+};
+
+use super::{
+    auth::AdminUser,
+    error::{ApiError, ErrorBody},
+};
+
+/// Dumps are far larger than the usual request body
+pub const IMPORT_BODY_LIMIT: usize = 512 * 1024 * 1024;
+
+#[derive(Deserialize, IntoParams)]
+pub struct ImportParams {
+    #[serde(default)]
+    pub mode: ImportMode,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/export",
+    tag = "admin",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Dump),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody)
+    )
+)]
+pub async fn get_export(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let dump = DumpController::new(&state.pool).export().await?;
+
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"drue-export.json\"",
+        )],
+        Json(dump),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/import",
+    tag = "admin",
+    params(ImportParams),
+    request_body = Dump,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = ImportSummary),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody),
+        (status = 409, description = "Restoring into a database with data", body = ErrorBody),
+        (status = 422, description = "Unsupported format version", body = ErrorBody)
+    )
+)]
+pub async fn post_import(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+    Query(params): Query<ImportParams>,
+    Json(dump): Json<Dump>,
+) -> Result<Json<ImportSummary>, ApiError> {
+    let summary = DumpController::new(&state.pool)
+        .import(&dump, params.mode)
+        .await?;
+
+    Ok(Json(summary))
+}
@@ -0,0 +1,182 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::error::ErrorKind;
+use utoipa::ToSchema;
+
+use crate::{
+    controllers::{collections::CollectionError, controller::SystemColumn, dump::ImportError},
+    llm::LlmError,
+};
+
+use super::etag;
+
+/// Error returned by handlers, sent as an `ErrorBody`.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+    pub details: Value,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code,
+            message: message.into(),
+            details: Value::Null,
+        }
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Stable, machine readable code such as `not_found`
+    pub error: &'static str,
+    pub message: String,
+    pub details: Value,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: self.code,
+            message: self.message,
+            details: self.details,
+        };
+
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => {
+                ApiError::new(StatusCode::NOT_FOUND, "not_found", "resource not found")
+            }
+            sqlx::Error::ColumnNotFound(column) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "unknown_column",
+                format!("there is no column {}", column),
+            )
+            .with_details(json!({ "column": column })),
+            // Raised for bodies that can't be turned into column values
+            sqlx::Error::Encode(err) => match err.downcast::<SystemColumn>() {
+                Ok(column) => {
+                    ApiError::new(StatusCode::BAD_REQUEST, "system_column", column.to_string())
+                        .with_details(json!({ "column": column.0 }))
+                }
+                Err(err) => ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "invalid_body",
+                    err.to_string(),
+                ),
+            },
+            sqlx::Error::Database(err) => match err.kind() {
+                ErrorKind::UniqueViolation => {
+                    ApiError::new(StatusCode::CONFLICT, "conflict", err.message())
+                }
+                ErrorKind::NotNullViolation
+                | ErrorKind::CheckViolation
+                | ErrorKind::ForeignKeyViolation => ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "constraint_violation",
+                    err.message(),
+                ),
+                _ => ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "database_error",
+                    err.message(),
+                ),
+            },
+            err => ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database_error",
+                err.to_string(),
+            ),
+        }
+    }
+}
+
+impl From<minijinja::Error> for ApiError {
+    fn from(err: minijinja::Error) -> Self {
+        ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_prompt_template",
+            err.to_string(),
+        )
+    }
+}
+
+impl From<LlmError> for ApiError {
+    fn from(err: LlmError) -> Self {
+        let message = err.to_string();
+
+        match err {
+            LlmError::Database(_) | LlmError::Template(_) => ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "prompt_template_error",
+                message,
+            ),
+            LlmError::Request(_) => {
+                ApiError::new(StatusCode::BAD_GATEWAY, "llm_unreachable", message)
+            }
+            LlmError::Provider(_) => {
+                ApiError::new(StatusCode::BAD_GATEWAY, "llm_provider_error", message)
+            }
+            LlmError::MalformedResponse(_) => {
+                ApiError::new(StatusCode::BAD_GATEWAY, "llm_malformed_response", message)
+            }
+            LlmError::Invalid(violations) => {
+                ApiError::new(StatusCode::BAD_GATEWAY, "llm_invalid_question", message)
+                    .with_details(json!(violations))
+            }
+        }
+    }
+}
+
+impl From<CollectionError> for ApiError {
+    fn from(err: CollectionError) -> Self {
+        match err {
+            CollectionError::Forbidden(required) => ApiError::new(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "your role on this collection does not allow this",
+            )
+            .with_details(json!({ "required_role": required })),
+            CollectionError::Stale => etag::stale(),
+            CollectionError::Database(err) => err.into(),
+        }
+    }
+}
+
+impl From<ImportError> for ApiError {
+    fn from(err: ImportError) -> Self {
+        match err {
+            ImportError::UnsupportedVersion(version) => ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "unsupported_export_version",
+                format!("exports of format version {} are not supported", version),
+            ),
+            ImportError::NotEmpty => ApiError::new(
+                StatusCode::CONFLICT,
+                "database_not_empty",
+                "restoring requires an empty database, merge instead",
+            ),
+            ImportError::Database(err) => err.into(),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::controllers::{collections::Collection, words::Word};
+
+use super::error::ApiError;
+
+pub fn word_etag(word: &Word) -> String {
+    format!("\"word-{}-{}\"", word.id, word.version)
+}
+
+/// Also covers the versions of the words, so the tag changes whenever the
+/// response body does. Smart collections rely on this, their word list
+/// changes without touching the collection.
+pub fn collection_etag(collection: &Collection) -> String {
+    let words = collection
+        .words
+        .iter()
+        .flat_map(|word| [word.id, word.version])
+        .flat_map(i64::to_le_bytes);
+
+    format!(
+        "\"collection-{}-{}-{:x}\"",
+        collection.id,
+        collection.version,
+        fnv1a(words)
+    )
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher`, its output is fixed by its
+/// definition, so tags clients hold survive a Rust upgrade.
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes.into_iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Whether the header lists `etag`. `If-None-Match` compares weakly, ignoring
+/// a `W/` prefix, while `If-Match` compares strongly and is never satisfied by
+/// a weak tag (RFC 9110, section 13.1).
+fn matches(headers: &HeaderMap, name: header::HeaderName, etag: &str) -> Option<bool> {
+    let weak = name == header::IF_NONE_MATCH;
+    let value = headers.get(name)?.to_str().ok()?;
+
+    Some(value.split(',').map(str::trim).any(|candidate| {
+        candidate == "*"
+            || match candidate.strip_prefix("W/") {
+                Some(candidate) => weak && candidate == etag,
+                None => candidate == etag,
+            }
+    }))
+}
+
+/// Rejects writes based on a stale copy. Requests without `If-Match` are let
+/// through so existing clients keep working. Returns whether the request is
+/// conditional, in which case the write has to check the version again, as
+/// someone else may write in between.
+pub fn check_if_match(headers: &HeaderMap, etag: &str) -> Result<bool, ApiError> {
+    match matches(headers, header::IF_MATCH, etag) {
+        Some(false) => Err(stale().with_details(json!({ "etag": etag }))),
+        Some(true) => Ok(true),
+        None => Ok(false),
+    }
+}
+
+/// The answer to a conditional write that lost against another one.
+pub fn stale() -> ApiError {
+    ApiError::new(
+        StatusCode::PRECONDITION_FAILED,
+        "precondition_failed",
+        "the resource was changed by someone else",
+    )
+}
+
+/// Responds with the body and its `ETag`, or an empty `304 Not Modified` if
+/// the client already has this version.
+pub fn tagged<T: Serialize>(headers: &HeaderMap, etag: String, body: T) -> Response {
+    let value = HeaderValue::from_str(&etag).unwrap();
+
+    if matches(headers, header::IF_NONE_MATCH, &etag) == Some(true) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, value)]).into_response();
+    }
+
+    ([(header::ETAG, value)], Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fnv1a;
+
+    #[test]
+    fn fnv1a_matches_the_reference_values() {
+        assert_eq!(fnv1a(*b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(*b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(*b"foobar"), 0x85944171f73967e8);
+    }
+}
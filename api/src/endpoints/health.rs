@@ -0,0 +1,167 @@
+//! Probes for the orchestrator. `/healthz` only tells that the process answers,
+//! `/readyz` whether it can serve requests, with the outcome of every check.
+//!
+//! There is no media directory to check: words only link to images and audio
+//! hosted elsewhere and nothing is uploaded. The backup directory is the only
+//! one the server writes to, so that is the directory checked for writability.
+
+use std::{
+    fs,
+    future::Future,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{controllers::maintenance::MaintenanceController, AppState, MIGRATOR};
+
+const LLM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Created and removed again to tell whether a directory is writable
+const PROBE_FILE: &str = ".drue-probe";
+
+#[derive(Serialize, ToSchema)]
+pub struct Health {
+    pub ok: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Check {
+    pub name: String,
+    pub ok: bool,
+    pub latency_ms: i64,
+    /// Why the check failed, or what it found
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: Vec<Check>,
+}
+
+#[derive(Deserialize, Clone, Debug, IntoParams)]
+pub struct ReadinessParams {
+    /// Also check that the LLM provider answers. Off by default, as it sends
+    /// a request to the provider on every probe.
+    pub llm: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "The process is alive", body = Health)
+    )
+)]
+pub async fn get_healthz() -> Json<Health> {
+    Json(Health { ok: true })
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "monitoring",
+    params(ReadinessParams),
+    responses(
+        (status = 200, description = "Every check passed", body = Readiness),
+        (status = 503, description = "At least one check failed", body = Readiness)
+    )
+)]
+pub async fn get_readyz(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ReadinessParams>,
+) -> (StatusCode, Json<Readiness>) {
+    let maintenance = MaintenanceController::new(&state.pool);
+
+    let mut checks = vec![
+        check("database", async {
+            maintenance.ping().await.map_err(|err| err.to_string())?;
+
+            Ok(None)
+        })
+        .await,
+        check("migrations", async {
+            let applied = maintenance
+                .applied_migrations()
+                .await
+                .map_err(|err| err.to_string())?;
+
+            let pending = MIGRATOR
+                .iter()
+                .filter(|migration| !migration.migration_type.is_down_migration())
+                .filter(|migration| !applied.contains(&migration.version))
+                .map(|migration| migration.version.to_string())
+                .collect::<Vec<_>>();
+
+            match pending.is_empty() {
+                true => Ok(None),
+                false => Err(format!("pending: {}", pending.join(", "))),
+            }
+        })
+        .await,
+    ];
+
+    // Stands in for the media directory, see the module docs
+    if let Some(backup) = &state.backup {
+        checks.push(check("backup_dir", async { writable(&backup.dir) }).await);
+    }
+
+    if params.llm.unwrap_or(false) {
+        checks.push(
+            check("llm", async {
+                // Any answer will do, even an error for the wrong method
+                let response = state
+                    .http_client
+                    .get(&state.llm_url)
+                    .timeout(LLM_TIMEOUT)
+                    .send()
+                    .await
+                    .map_err(|err| err.without_url().to_string())?;
+
+                Ok(Some(format!("answered {}", response.status().as_u16())))
+            })
+            .await,
+        );
+    }
+
+    let ready = checks.iter().all(|check| check.ok);
+    let status = match ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (status, Json(Readiness { ready, checks }))
+}
+
+async fn check(name: &str, run: impl Future<Output = Result<Option<String>, String>>) -> Check {
+    let started = Instant::now();
+    let result = run.await;
+
+    Check {
+        name: name.to_string(),
+        ok: result.is_ok(),
+        latency_ms: started.elapsed().as_millis() as i64,
+        detail: result.unwrap_or_else(Some),
+    }
+}
+
+fn writable(dir: &Path) -> Result<Option<String>, String> {
+    let probe = dir.join(PROBE_FILE);
+
+    fs::create_dir_all(dir)
+        .and_then(|()| fs::write(&probe, b""))
+        .and_then(|()| fs::remove_file(&probe))
+        .map_err(|err| err.to_string())?;
+
+    Ok(None)
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    controllers::llm_calls::{LlmCallsController, LlmDailyStats},
+    endpoints::{
+        auth::AdminUser,
+        error::{ApiError, ErrorBody},
+    },
+    AppState,
+};
+
+#[derive(Deserialize, Clone, Debug, IntoParams)]
+pub struct StatsParams {
+    pub days: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct LlmStats {
+    pub days: Vec<LlmDailyStats>,
+    pub total_tokens: i64,
+    pub total_cost: f64,
+    pub tokens_today: i64,
+    pub daily_token_budget: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/llm_stats",
+    tag = "admin",
+    params(StatsParams),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = LlmStats),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody)
+    )
+)]
+pub async fn get_llm_stats(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<StatsParams>,
+) -> Result<Json<LlmStats>, ApiError> {
+    let controller = LlmCallsController::new(&state.pool);
+
+    let mut days = controller.daily_stats(params.days.unwrap_or(30)).await?;
+
+    for day in days.iter_mut() {
+        day.cost = (day.prompt_tokens as f64 * state.prompt_token_price
+            + day.completion_tokens as f64 * state.completion_token_price)
+            / 1_000_000.0;
+    }
+
+    let total_tokens = days
+        .iter()
+        .map(|day| day.prompt_tokens + day.completion_tokens)
+        .sum();
+    let total_cost = days.iter().fold(0.0, |total, day| total + day.cost);
+
+    let tokens_today = controller.tokens_today(None).await?;
+
+    Ok(Json(LlmStats {
+        days,
+        total_tokens,
+        total_cost,
+        tokens_today,
+        daily_token_budget: state.daily_token_budget,
+    }))
+}
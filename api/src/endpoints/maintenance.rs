@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{
+    controllers::maintenance::{Checkpoint, MaintenanceController},
+    jobs::backup,
+    AppState,
+};
+
+use super::{
+    auth::AdminUser,
+    error::{ApiError, ErrorBody},
+};
+
+#[derive(Serialize, ToSchema)]
+pub struct Integrity {
+    pub ok: bool,
+    pub problems: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Backup {
+    pub path: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/integrity",
+    tag = "admin",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Integrity),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody)
+    )
+)]
+pub async fn get_integrity(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+) -> Result<Json<Integrity>, ApiError> {
+    let problems = MaintenanceController::new(&state.pool)
+        .integrity_check()
+        .await?;
+
+    Ok(Json(Integrity {
+        ok: problems.is_empty(),
+        problems,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/checkpoint",
+    tag = "admin",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Checkpoint),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody)
+    )
+)]
+pub async fn post_checkpoint(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+) -> Result<Json<Checkpoint>, ApiError> {
+    let checkpoint = MaintenanceController::new(&state.pool).checkpoint().await?;
+
+    Ok(Json(checkpoint))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/backups",
+    tag = "admin",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Backup),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody),
+        (status = 409, description = "Backups are disabled", body = ErrorBody)
+    )
+)]
+pub async fn post_backup(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+) -> Result<Json<Backup>, ApiError> {
+    let config = state.backup.as_ref().ok_or_else(|| {
+        ApiError::new(
+            StatusCode::CONFLICT,
+            "backups_disabled",
+            "set BACKUP_DIR to enable backups",
+        )
+    })?;
+
+    let path = backup::run(&state, config)
+        .await
+        .map_err(|err| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "backup_failed", err))?;
+
+    Ok(Json(Backup {
+        path: path.to_string_lossy().to_string(),
+    }))
+}
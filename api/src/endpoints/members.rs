@@ -0,0 +1,256 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    controllers::{
+        activity::{self, Activity, ActivityController},
+        collections::{Collection, CollectionError, CollectionsController},
+        controller::Controller,
+        members::{CreateInvite, Invite, Member, MembersController, Role},
+    },
+    AppState,
+};
+
+use super::{
+    auth::AuthUser,
+    error::{ApiError, ErrorBody},
+};
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetRole {
+    pub role: Role,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ActivityParams {
+    pub limit: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}/members",
+    tag = "members",
+    params(("id" = i64, Path)),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Vec<Member>),
+        (status = 403, description = "Role too low", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_members(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Member>>, ApiError> {
+    let collections = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let collection = collections.get_info(id).await?;
+    collections
+        .authorize(
+            &collection,
+            user.map(|AuthUser(user)| user.id),
+            Role::Viewer,
+        )
+        .await?;
+
+    let members = MembersController::new(&state.pool)
+        .get_for_collection(id)
+        .await?;
+
+    Ok(Json(members))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/collections/{id}/members/{user_id}",
+    tag = "members",
+    params(("id" = i64, Path), ("user_id" = i64, Path)),
+    request_body = SetRole,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Vec<Member>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role too low", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn put_member(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path((id, member_id)): Path<(i64, i64)>,
+    Json(body): Json<SetRole>,
+) -> Result<Json<Vec<Member>>, ApiError> {
+    let collections = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let collection = collections.get_info(id).await?;
+    collections
+        .authorize(&collection, Some(user.id), Role::Owner)
+        .await?;
+
+    // The creator stays owner, so a collection can not end up without one
+    if collection.owner_id == Some(member_id) {
+        return Err(CollectionError::Forbidden(Role::Owner).into());
+    }
+
+    let members = MembersController::new(&state.pool);
+    members.set_role(id, member_id, body.role).await?;
+
+    Ok(Json(members.get_for_collection(id).await?))
+}
+
+/// Owners can remove anyone but the creator, everyone else can only leave.
+#[utoipa::path(
+    delete,
+    path = "/api/collections/{id}/members/{user_id}",
+    tag = "members",
+    params(("id" = i64, Path), ("user_id" = i64, Path)),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Vec<Member>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role too low", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn delete_member(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path((id, member_id)): Path<(i64, i64)>,
+) -> Result<Json<Vec<Member>>, ApiError> {
+    let collections = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let collection = collections.get_info(id).await?;
+
+    if collection.owner_id == Some(member_id) {
+        return Err(CollectionError::Forbidden(Role::Owner).into());
+    }
+
+    if member_id != user.id {
+        collections
+            .authorize(&collection, Some(user.id), Role::Owner)
+            .await?;
+    }
+
+    let members = MembersController::new(&state.pool);
+
+    if members.remove(id, member_id).await? == 0 {
+        return Err(sqlx::Error::RowNotFound.into());
+    }
+
+    ActivityController::new(&state.pool)
+        .log(id, Some(member_id), activity::MEMBER_REMOVED, None)
+        .await?;
+
+    Ok(Json(members.get_for_collection(id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/collections/{id}/invites",
+    tag = "members",
+    params(("id" = i64, Path)),
+    request_body = CreateInvite,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Invite),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Role too low", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn post_invite(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path(id): Path<i64>,
+    Json(body): Json<CreateInvite>,
+) -> Result<Json<Invite>, ApiError> {
+    let collections = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let collection = collections.get_info(id).await?;
+    collections
+        .authorize(&collection, Some(user.id), Role::Owner)
+        .await?;
+
+    if body.role == Role::Owner {
+        return Err(CollectionError::Forbidden(Role::Owner).into());
+    }
+
+    let invite = MembersController::new(&state.pool)
+        .create_invite(id, user.id, &body)
+        .await?;
+
+    Ok(Json(invite))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/invites/{token}",
+    tag = "members",
+    params(("token" = String, Path)),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Collection),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Unknown or expired invite", body = ErrorBody)
+    )
+)]
+pub async fn accept_invite(
+    State(state): State<Arc<AppState>>,
+    AuthUser(user): AuthUser,
+    Path(token): Path<String>,
+) -> Result<Json<Collection>, ApiError> {
+    let id = MembersController::new(&state.pool)
+        .accept_invite(&token, user.id)
+        .await?;
+
+    ActivityController::new(&state.pool)
+        .log(id, Some(user.id), activity::MEMBER_JOINED, None)
+        .await?;
+
+    let collection = CollectionsController::new(&state.pool).get_one(id).await?;
+
+    Ok(Json(collection))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/collections/{id}/activity",
+    tag = "members",
+    params(("id" = i64, Path), ActivityParams),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Vec<Activity>),
+        (status = 403, description = "Role too low", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_activity(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    Path(id): Path<i64>,
+    Query(params): Query<ActivityParams>,
+) -> Result<Json<Vec<Activity>>, ApiError> {
+    let collections = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let collection = collections.get_info(id).await?;
+    collections
+        .authorize(
+            &collection,
+            user.map(|AuthUser(user)| user.id),
+            Role::Viewer,
+        )
+        .await?;
+
+    let activity = ActivityController::new(&state.pool)
+        .get_for_collection(id, params.limit.unwrap_or(100).clamp(1, 1000))
+        .await?;
+
+    Ok(Json(activity))
+}
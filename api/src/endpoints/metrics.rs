@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::{metrics, AppState};
+
+use super::error::ApiError;
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "monitoring",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", body = String, content_type = "text/plain")
+    )
+)]
+pub async fn get_metrics(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let body = state.metrics.render(&state.pool).await?;
+
+    Ok(([(header::CONTENT_TYPE, metrics::CONTENT_TYPE)], body))
+}
@@ -1,2 +1,16 @@
+pub mod auth;
 pub mod collections;
+pub mod docs;
+pub mod dump;
+pub mod error;
+pub mod etag;
+pub mod health;
+pub mod llm_calls;
+pub mod maintenance;
+pub mod members;
+pub mod metrics;
+pub mod prompt_templates;
+pub mod tags;
+pub mod trash;
+pub mod users;
 pub mod words;
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    controllers::{
+        collections::CollectionsController,
+        controller::Controller,
+        prompt_templates::{CreatePromptTemplate, PromptTemplate, PromptTemplatesController},
+        words::WordsController,
+    },
+    endpoints::{
+        auth::AdminUser,
+        error::{ApiError, ErrorBody},
+    },
+    llm::prompt::{render, PromptContext, RenderedPrompt},
+    AppState,
+};
+
+#[derive(Deserialize, Clone, Debug, IntoParams)]
+pub struct PreviewParams {
+    pub word_id: i64,
+    pub collection_id: Option<i64>,
+    pub level: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/prompt_templates",
+    tag = "prompt templates",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Vec<PromptTemplate>),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody)
+    )
+)]
+pub async fn get_all_prompt_templates(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+) -> Result<Json<Vec<PromptTemplate>>, ApiError> {
+    let controller = PromptTemplatesController::new(&state.pool);
+
+    let templates = controller.get_all().await?;
+
+    Ok(Json(templates))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/prompt_templates/{id}",
+    tag = "prompt templates",
+    security(("bearer" = [])),
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "OK", body = PromptTemplate),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_one_prompt_template(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+    Path(id): Path<i64>,
+) -> Result<Json<PromptTemplate>, ApiError> {
+    let controller = PromptTemplatesController::new(&state.pool);
+
+    let template = controller.get_one(id).await?;
+
+    Ok(Json(template))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/prompt_templates",
+    tag = "prompt templates",
+    security(("bearer" = [])),
+    request_body = CreatePromptTemplate,
+    responses(
+        (status = 200, description = "The new version", body = PromptTemplate),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody)
+    )
+)]
+pub async fn post_prompt_template(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+    Json(template): Json<CreatePromptTemplate>,
+) -> Result<Json<PromptTemplate>, ApiError> {
+    let controller = PromptTemplatesController::new(&state.pool);
+
+    let id = controller.create_version(template).await?;
+
+    let template = controller.get_one(id).await?;
+
+    Ok(Json(template))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/prompt_templates/{id}/preview",
+    tag = "prompt templates",
+    security(("bearer" = [])),
+    params(("id" = i64, Path), PreviewParams),
+    responses(
+        (status = 200, description = "OK", body = RenderedPrompt),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not an administrator", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 422, description = "The template does not render", body = ErrorBody)
+    )
+)]
+pub async fn preview_prompt_template(
+    State(state): State<Arc<AppState>>,
+    _admin: AdminUser,
+    Path(id): Path<i64>,
+    Query(params): Query<PreviewParams>,
+) -> Result<Json<RenderedPrompt>, ApiError> {
+    let template = PromptTemplatesController::new(&state.pool)
+        .get_one(id)
+        .await?;
+
+    let words = WordsController::new(&state.pool);
+
+    let word = words.get_one(params.word_id).await?;
+    let spellings = words.get_spellings(params.word_id).await?;
+
+    let collection = match params.collection_id {
+        Some(collection_id) => Some(
+            CollectionsController::new(&state.pool)
+                .get_info(collection_id)
+                .await?,
+        ),
+        None => None,
+    };
+
+    let level = params.level.as_deref().unwrap_or(&state.learner_level);
+
+    let context = PromptContext::new(&word, &spellings, collection.as_ref(), level);
+
+    let prompt = render(&template, &context)?;
+
+    Ok(Json(prompt))
+}
@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde_json::Value;
+
+use crate::{
+    controllers::{
+        controller::Controller,
+        tags::{Tag, TagsController},
+    },
+    endpoints::error::{ApiError, ErrorBody},
+    AppState,
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/tags",
+    tag = "tags",
+    responses(
+        (status = 200, description = "OK", body = Vec<Tag>)
+    )
+)]
+pub async fn get_all_tags(State(state): State<Arc<AppState>>) -> Result<Json<Vec<Tag>>, ApiError> {
+    let controller = TagsController::new(&state.pool);
+
+    let tags = controller.get_all().await?;
+
+    Ok(Json(tags))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tags/{id}",
+    tag = "tags",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "OK", body = Tag),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_one_tag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Tag>, ApiError> {
+    let controller = TagsController::new(&state.pool);
+
+    let tag = controller.get_one(id).await?;
+
+    Ok(Json(tag))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/tags",
+    tag = "tags",
+    request_body = Object,
+    responses(
+        (status = 200, description = "OK, or restored from the trash", body = Tag),
+        (status = 400, description = "Sets a column the server maintains", body = ErrorBody),
+        (status = 409, description = "Name taken", body = ErrorBody),
+        (status = 422, description = "A column is unknown, missing or violates a constraint", body = ErrorBody)
+    )
+)]
+pub async fn post_tag(
+    State(state): State<Arc<AppState>>,
+    Json(tag): Json<Value>,
+) -> Result<Json<Tag>, ApiError> {
+    let controller = TagsController::new(&state.pool);
+
+    let id = controller.create(tag).await?;
+
+    let tag = controller.get_one(id).await?;
+
+    Ok(Json(tag))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/tags/{id}",
+    tag = "tags",
+    params(("id" = i64, Path)),
+    request_body = Object,
+    responses(
+        (status = 200, description = "OK", body = Tag),
+        (status = 400, description = "Sets a column the server maintains", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 422, description = "A column is unknown, missing or violates a constraint", body = ErrorBody)
+    )
+)]
+pub async fn put_tag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(tag): Json<Value>,
+) -> Result<Json<Tag>, ApiError> {
+    let controller = TagsController::new(&state.pool);
+
+    controller.get_one(id).await?;
+    controller.update(id, tag).await?;
+
+    let tag = controller.get_one(id).await?;
+
+    Ok(Json(tag))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/tags/{id}",
+    tag = "tags",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "Moved to the trash", body = ()),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn delete_tag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<()>, ApiError> {
+    let controller = TagsController::new(&state.pool);
+
+    controller.get_one(id).await?;
+    controller.delete(id).await?;
+
+    Ok(Json(()))
+}
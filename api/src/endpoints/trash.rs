@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+use crate::{
+    controllers::trash::{TrashController, TrashItem, TrashKind},
+    AppState,
+};
+
+use super::{
+    auth::AuthUser,
+    error::{ApiError, ErrorBody},
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/trash",
+    tag = "trash",
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Vec<TrashItem>)
+    )
+)]
+pub async fn get_trash(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+) -> Result<Json<Vec<TrashItem>>, ApiError> {
+    let items = TrashController::new(&state.pool)
+        .get_all(user.map(|AuthUser(user)| user.id))
+        .await?;
+
+    Ok(Json(items))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/trash/{kind}/{id}/restore",
+    tag = "trash",
+    params(("kind" = TrashKind, Path), ("id" = i64, Path)),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = ()),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn restore_from_trash(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    Path((kind, id)): Path<(TrashKind, i64)>,
+) -> Result<Json<()>, ApiError> {
+    TrashController::new(&state.pool)
+        .restore(kind, id, user.map(|AuthUser(user)| user.id))
+        .await?;
+
+    Ok(Json(()))
+}
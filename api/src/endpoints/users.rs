@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    controllers::{
+        controller::Controller,
+        users::{Credentials, User, UsersController},
+    },
+    endpoints::{
+        auth::AuthUser,
+        error::{ApiError, ErrorBody},
+    },
+    AppState,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Session {
+    pub token: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users",
+    tag = "users",
+    request_body = Credentials,
+    responses(
+        (status = 200, description = "OK", body = User),
+        (status = 409, description = "Name taken or reserved", body = ErrorBody)
+    )
+)]
+pub async fn post_user(
+    State(state): State<Arc<AppState>>,
+    Json(credentials): Json<Credentials>,
+) -> Result<Json<User>, ApiError> {
+    // Administrators are configured by name, so nobody else may claim one of
+    // those names before the account exists. Create it with `drue users add`.
+    if state.admin_users.contains(&credentials.name) {
+        return Err(ApiError::new(
+            StatusCode::CONFLICT,
+            "name_reserved",
+            "user name is reserved",
+        ));
+    }
+
+    let controller = UsersController::new(&state.pool);
+
+    let id = controller
+        .register(&credentials)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::Database(err) if err.is_unique_violation() => ApiError::new(
+                StatusCode::CONFLICT,
+                "name_taken",
+                "user name is already taken",
+            ),
+            err => err.into(),
+        })?;
+
+    let user = controller.get_one(id).await?;
+
+    Ok(Json(user))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sessions",
+    tag = "users",
+    request_body = Credentials,
+    responses(
+        (status = 200, description = "OK", body = Session),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn post_session(
+    State(state): State<Arc<AppState>>,
+    Json(credentials): Json<Credentials>,
+) -> Result<Json<Session>, ApiError> {
+    let controller = UsersController::new(&state.pool);
+
+    let token = controller.login(&credentials).await.map_err(|_| {
+        ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "wrong name or password",
+        )
+    })?;
+
+    Ok(Json(Session { token }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me",
+    tag = "users",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = User),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody)
+    )
+)]
+pub async fn get_me(AuthUser(user): AuthUser) -> Json<User> {
+    Json(user)
+}
@@ -1,203 +1,566 @@
 use std::sync::Arc;
 
 use axum::{
-    debug_handler,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
     Json,
 };
+use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     controllers::{
+        collections::CollectionsController,
         controller::Controller,
-        words::{Word, WordsController},
+        questions::{Question, QuestionsController},
+        reviews::{Review, ReviewsController},
+        revisions::{Revision, RevisionsController},
+        tags::{Tag, TagsController},
+        words::{DuplicateGroup, Spelling, Word, WordFilter, WordsController},
     },
-    AppState,
+    endpoints::{
+        auth::AuthUser,
+        error::{ApiError, ErrorBody},
+        etag,
+    },
+    jobs::pregenerate,
+    languages::grading::{Direction, Grade},
+    llm::{self, offline, prompt::PromptContext, MULTIPLE_CHOICE},
+    metrics, AppState,
 };
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct Option {
-    pub name: String,
-    pub correct: bool,
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Answer {
+    pub answer: String,
+    #[serde(default)]
+    pub direction: Direction,
+    pub collection_id: Option<i64>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct Question {
-    pub question: String,
-    pub options: Vec<Option>,
+#[derive(Deserialize, Clone, Debug, IntoParams)]
+pub struct WordsParams {
+    pub tag: Option<String>,
+    /// Page size, all words are returned without one
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
 }
 
-pub async fn get_all_words(State(state): State<Arc<AppState>>) -> Json<Vec<Word>> {
-    let controller = WordsController::new(&state.pool);
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Merge {
+    pub duplicates: Vec<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/words",
+    tag = "words",
+    params(WordsParams),
+    responses(
+        (status = 200, description = "OK", body = Vec<Word>)
+    )
+)]
+pub async fn get_all_words(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<WordsParams>,
+) -> Result<Json<Vec<Word>>, ApiError> {
+    let filter = WordFilter {
+        tags: params.tag.into_iter().collect(),
+        ..Default::default()
+    };
 
-    let words = controller.get_all().await.unwrap();
+    let words = WordsController::new(&state.pool)
+        .get_page(
+            &filter,
+            params.limit.map(|limit| limit.clamp(1, MAX_PAGE_SIZE)),
+            params.offset.unwrap_or(0).max(0),
+        )
+        .await?;
 
-    Json(words)
+    Ok(Json(words))
 }
 
-pub async fn get_one_word(State(state): State<Arc<AppState>>, Path(id): Path<i64>) -> Json<Word> {
+#[utoipa::path(
+    get,
+    path = "/api/words/{id}",
+    tag = "words",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "OK", body = Word, headers(("etag" = String))),
+        (status = 304, description = "Matches If-None-Match"),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_one_word(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> Result<Response, ApiError> {
     let controller = WordsController::new(&state.pool);
 
-    let word = controller.get_one(id).await.unwrap();
+    let word = controller.get_one(id).await?;
 
-    Json(word)
+    Ok(etag::tagged(&headers, etag::word_etag(&word), word))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/question/{id}",
+    tag = "practice",
+    params(("id" = i64, Path, description = "Collection to pick a word from")),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Question),
+        (status = 404, description = "Not found, or too few words for a question once the token budget is spent", body = ErrorBody),
+        (status = 502, description = "The LLM failed to produce a valid question", body = ErrorBody)
+    )
+)]
 pub async fn generate_question(
     State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
     Path(id): Path<i64>,
-) -> Json<Question> {
+) -> Result<Json<Question>, ApiError> {
+    let user_id = user.map(|AuthUser(user)| user.id);
     let controller = WordsController::new(&state.pool);
+    let questions = QuestionsController::new(&state.pool);
 
-    let word = controller.get_random_word(id).await.unwrap();
-
-    let body = format!(
-        r#"{{
-  "model": "gpt-4o-2024-08-06",
-  "messages": [
-    {{
-      "role": "system",
-      "content": [
-        {{
-          "type": "text",
-          "text": "I'm building a Norwegian learning app. Please suggest me a question related to the word below and three to five other single word options  which the user can choose from. One or more options should be correct. The question should be in English. Don't ask what the word means in English."
-        }}
-      ]
-    }},
-    {{
-      "role": "user",
-      "content": [
-        {{
-          "type": "text",
-          "text": "The word is '{}'"
-        }}
-      ]
-    }}
-  ],
-  "temperature": 1.52,
-  "max_tokens": 2048,
-  "top_p": 1,
-  "frequency_penalty": 0.61,
-  "presence_penalty": 0,
-  "response_format": {{
-    "type": "json_schema",
-    "json_schema": {{
-      "name": "response",
-      "strict": true,
-      "schema": {{
-        "type": "object",
-        "properties": {{
-          "question": {{
-            "type": "string"
-          }},
-          "options": {{
-            "type": "array",
-            "items": {{
-              "type": "object",
-              "properties": {{
-                "name": {{
-                  "type": "string"
-                }},
-                "correct": {{
-                  "type": "boolean"
-                }}
-              }},
-              "additionalProperties": false,
-              "required": [
-                "name",
-                "correct"
-              ]
-            }}
-          }}
-        }},
-        "additionalProperties": false,
-        "required": [
-          "question",
-          "options"
-        ]
-      }}
-    }}
-  }}
-}}"#,
-        word.word
-    );
-
-    println!("{}", body);
-
-    let response = state
-        .http_client
-        .post("https://api.openai.com/v1/chat/completions")
-        .body(body)
-        .send()
-        .await
-        .unwrap()
-        .text()
+    let collections = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+
+    let mut collection = collections.get_one(id).await?;
+
+    // Private collections of others are reported as missing, not forbidden
+    if collections.role_of(&collection, user_id).await?.is_none() {
+        return Err(sqlx::Error::RowNotFound.into());
+    }
+
+    let words = std::mem::take(&mut collection.words);
+    let (source_language, target_language) =
+        (&collection.source_language, &collection.target_language);
+
+    let word = words
+        .choose(&mut rand::thread_rng())
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    let question = match questions
+        .next_for_word(word.id, MULTIPLE_CHOICE, source_language, target_language)
         .await
-        .unwrap();
+    {
+        Ok(question) => {
+            state.metrics.record_question(metrics::FROM_POOL);
+            question
+        }
+        // Nothing pre-generated for this word yet, generate one on demand
+        Err(sqlx::Error::RowNotFound) => {
+            let spellings = controller.get_spellings(word.id).await?;
+            let context =
+                PromptContext::new(word, &spellings, Some(&collection), &state.learner_level);
+
+            if !llm::within_budget(&state, user_id).await? {
+                let mut distractors = words
+                    .iter()
+                    .filter(|distractor| distractor.id != word.id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                distractors.shuffle(&mut rand::thread_rng());
+
+                let question =
+                    offline::generate_question(&context, &distractors).map_err(|violations| {
+                        ApiError::new(
+                            StatusCode::NOT_FOUND,
+                            "not_enough_words",
+                            "the collection has too few distinct words to ask without the LLM",
+                        )
+                        .with_details(json!(violations))
+                    })?;
+
+                state.metrics.record_question(metrics::OFFLINE);
+                return Ok(Json(question));
+            }
 
-    let response: Value = serde_json::from_str(&response).unwrap();
+            let generation =
+                llm::generate_question(&state, MULTIPLE_CHOICE, &context, user_id).await?;
 
-    let question = response
-        .as_object()
-        .unwrap()
-        .get("choices")
-        .unwrap()
-        .as_array()
-        .unwrap()[0]
-        .as_object()
-        .unwrap()
-        .get("message")
-        .unwrap()
-        .as_object()
-        .unwrap()
-        .get("content")
-        .unwrap()
-        .as_str()
-        .unwrap();
+            questions
+                .store(
+                    word.id,
+                    MULTIPLE_CHOICE,
+                    source_language,
+                    target_language,
+                    generation.template_id,
+                    &generation.question,
+                )
+                .await?;
 
-    println!("{}", question);
+            state.metrics.record_question(metrics::GENERATED);
+            generation.question
+        }
+        Err(err) => return Err(err.into()),
+    };
 
-    let question: Question = serde_json::from_str(question).unwrap();
+    pregenerate::spawn(state.clone(), collection, vec![word.id]);
 
-    Json(question)
+    Ok(Json(question))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/words/{id}/answer",
+    tag = "practice",
+    params(("id" = i64, Path)),
+    request_body = Answer,
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Grade),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn post_answer(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    Path(id): Path<i64>,
+    Json(answer): Json<Answer>,
+) -> Result<Json<Grade>, ApiError> {
+    let user_id = user.map(|AuthUser(user)| user.id);
+
+    // Reviews only count towards collections the learner can see
+    if let Some(collection_id) = answer.collection_id {
+        let collections = CollectionsController::new(&state.pool).with_admins(&state.admin_users);
+        let collection = collections.get_info(collection_id).await?;
+
+        if collections.role_of(&collection, user_id).await?.is_none() {
+            return Err(sqlx::Error::RowNotFound.into());
+        }
+    }
+
+    let grade = WordsController::new(&state.pool)
+        .answer(
+            id,
+            user_id,
+            answer.collection_id,
+            answer.direction,
+            &answer.answer,
+        )
+        .await?;
+
+    Ok(Json(grade))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/words/{id}/spellings",
+    tag = "words",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "OK", body = Vec<Spelling>)
+    )
+)]
+pub async fn get_spellings(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Spelling>>, ApiError> {
+    let spellings = WordsController::new(&state.pool).get_spellings(id).await?;
+
+    Ok(Json(spellings))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/words/{id}/spellings",
+    tag = "words",
+    params(("id" = i64, Path)),
+    request_body = Vec<Spelling>,
+    responses(
+        (status = 200, description = "OK", body = Vec<Spelling>)
+    )
+)]
+pub async fn put_spellings(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(spellings): Json<Vec<Spelling>>,
+) -> Result<Json<Vec<Spelling>>, ApiError> {
+    let controller = WordsController::new(&state.pool);
+
+    controller.set_spellings(id, &spellings).await?;
+
+    let spellings = controller.get_spellings(id).await?;
+
+    Ok(Json(spellings))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/words/duplicates",
+    tag = "words",
+    responses(
+        (status = 200, description = "OK", body = Vec<DuplicateGroup>)
+    )
+)]
+pub async fn get_duplicate_words(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DuplicateGroup>>, ApiError> {
+    let duplicates = WordsController::new(&state.pool).get_duplicates().await?;
+
+    Ok(Json(duplicates))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/words/{id}/merge",
+    tag = "words",
+    params(("id" = i64, Path)),
+    request_body = Merge,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "The surviving word", body = Word),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn post_merge(
+    State(state): State<Arc<AppState>>,
+    // The duplicates are deleted for good rather than moved to the trash
+    _user: AuthUser,
+    Path(id): Path<i64>,
+    Json(merge): Json<Merge>,
+) -> Result<Json<Word>, ApiError> {
+    let controller = WordsController::new(&state.pool);
+
+    controller.merge(id, &merge.duplicates).await?;
+
+    let word = controller.get_one(id).await?;
+
+    Ok(Json(word))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/words/{id}/reviews",
+    tag = "practice",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "OK", body = Vec<Review>)
+    )
+)]
+pub async fn get_word_reviews(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Review>>, ApiError> {
+    let reviews = ReviewsController::new(&state.pool).get_for_word(id).await?;
+
+    Ok(Json(reviews))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/words/{id}/tags",
+    tag = "tags",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "OK", body = Vec<Tag>),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_word_tags(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Tag>>, ApiError> {
+    WordsController::new(&state.pool).get_one(id).await?;
+
+    let tags = TagsController::new(&state.pool).get_for_word(id).await?;
+
+    Ok(Json(tags))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/words/{id}/revisions",
+    tag = "words",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "OK", body = Vec<Revision>),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn get_word_revisions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Revision>>, ApiError> {
+    WordsController::new(&state.pool).get_one(id).await?;
+
+    let revisions = RevisionsController::new(&state.pool)
+        .get_for_word(id)
+        .await?;
+
+    Ok(Json(revisions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/words/{id}/revisions/{revision_id}/revert",
+    tag = "words",
+    params(("id" = i64, Path), ("revision_id" = i64, Path)),
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Word, headers(("etag" = String))),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 412, description = "Changed since the given ETag", body = ErrorBody)
+    )
+)]
+pub async fn revert_word(
+    State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+    Path((id, revision_id)): Path<(i64, i64)>,
+) -> Result<Response, ApiError> {
+    let controller = WordsController::new(&state.pool);
+
+    let word = controller.get_one(id).await?;
+    let version = etag::check_if_match(&headers, &etag::word_etag(&word))?.then_some(word.version);
+
+    let reverted = controller
+        .revert(id, revision_id, user.map(|AuthUser(user)| user.id), version)
+        .await?;
+
+    if version.is_some() && reverted == 0 {
+        return Err(etag::stale());
+    }
+
+    let word = controller.get_one(id).await?;
+
+    Ok(etag::tagged(
+        &HeaderMap::new(),
+        etag::word_etag(&word),
+        word,
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/words/{id}/tags",
+    tag = "tags",
+    params(("id" = i64, Path)),
+    request_body = Vec<String>,
+    responses(
+        (status = 200, description = "OK", body = Vec<Tag>),
+        (status = 404, description = "Not found", body = ErrorBody)
+    )
+)]
+pub async fn put_word_tags(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+    Json(names): Json<Vec<String>>,
+) -> Result<Json<Vec<Tag>>, ApiError> {
+    WordsController::new(&state.pool).get_one(id).await?;
+
+    let controller = TagsController::new(&state.pool);
+
+    controller.set_for_word(id, &names).await?;
+
+    let tags = controller.get_for_word(id).await?;
+
+    Ok(Json(tags))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/words",
+    tag = "words",
+    request_body = Object,
+    responses(
+        (status = 200, description = "OK", body = Word),
+        (status = 400, description = "Sets a column the server maintains", body = ErrorBody),
+        (status = 422, description = "A column is unknown, missing or violates a constraint", body = ErrorBody)
+    )
+)]
 pub async fn post_word(
     State(state): State<Arc<AppState>>,
-    Json(mut word): Json<Value>,
-) -> Json<Word> {
+    Json(word): Json<Value>,
+) -> Result<Json<Word>, ApiError> {
     let controller = WordsController::new(&state.pool);
 
-    let id = controller.create(word).await.unwrap();
+    let id = controller.create(word).await?;
 
-    let word = controller.get_one(id).await.unwrap();
+    let word = controller.get_one(id).await?;
 
-    Json(word)
+    Ok(Json(word))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/words/{id}",
+    tag = "words",
+    params(("id" = i64, Path)),
+    request_body = Object,
+    security((), ("bearer" = [])),
+    responses(
+        (status = 200, description = "OK", body = Word, headers(("etag" = String))),
+        (status = 400, description = "Sets a column the server maintains", body = ErrorBody),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 412, description = "Changed since the given ETag", body = ErrorBody),
+        (status = 422, description = "A column is unknown, missing or violates a constraint", body = ErrorBody)
+    )
+)]
 pub async fn put_word(
     State(state): State<Arc<AppState>>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
     Path(id): Path<i64>,
-    Json(mut word): Json<Value>,
-) -> Json<Word> {
+    Json(word): Json<Value>,
+) -> Result<Response, ApiError> {
     let controller = WordsController::new(&state.pool);
 
-    controller.update(id, word).await.unwrap();
+    let before = controller.get_one(id).await?;
+    let version =
+        etag::check_if_match(&headers, &etag::word_etag(&before))?.then_some(before.version);
+
+    let updated = controller
+        .update_as(id, user.map(|AuthUser(user)| user.id), version, word)
+        .await?;
+
+    if version.is_some() && updated == 0 {
+        return Err(etag::stale());
+    }
 
-    let word = controller.get_one(id).await.unwrap();
+    let word = controller.get_one(id).await?;
 
-    Json(word)
+    Ok(etag::tagged(
+        &HeaderMap::new(),
+        etag::word_etag(&word),
+        word,
+    ))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/words/{id}",
+    tag = "words",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, description = "Moved to the trash", body = ()),
+        (status = 404, description = "Not found", body = ErrorBody),
+        (status = 412, description = "Changed since the given ETag", body = ErrorBody)
+    )
+)]
 pub async fn delete_word(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Path(id): Path<i64>,
-) -> Result<Json<()>, StatusCode> {
+) -> Result<Json<()>, ApiError> {
     let controller = WordsController::new(&state.pool);
 
-    controller.delete(id).await.unwrap();
+    let word = controller.get_one(id).await?;
+    let version = etag::check_if_match(&headers, &etag::word_etag(&word))?.then_some(word.version);
+
+    let deleted = controller.delete_at(id, version).await?;
+
+    if version.is_some() && deleted == 0 {
+        return Err(etag::stale());
+    }
 
     Ok(Json(()))
 }
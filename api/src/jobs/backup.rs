@@ -0,0 +1,82 @@
+use std::{fs, io, path::PathBuf, sync::Arc, time::Duration};
+
+use crate::{controllers::maintenance::MaintenanceController, AppState};
+
+const PREFIX: &str = "drue-";
+const EXTENSION: &str = ".db";
+
+/// Where and how often backups are taken, configured by `BACKUP_DIR`,
+/// `BACKUP_INTERVAL_HOURS` and `BACKUP_KEEP`.
+#[derive(Clone, Debug)]
+pub struct BackupConfig {
+    pub dir: PathBuf,
+    pub interval: Duration,
+    /// Number of backups kept, older ones are removed
+    pub keep: usize,
+}
+
+/// Takes a backup named after the current time and removes the ones beyond
+/// `keep`. Returns the path of the new backup.
+pub async fn run(state: &AppState, config: &BackupConfig) -> Result<PathBuf, String> {
+    fs::create_dir_all(&config.dir).map_err(|err| err.to_string())?;
+
+    let stamp: String = sqlx::query_scalar("SELECT strftime('%Y%m%d-%H%M%S', 'now')")
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let path = config.dir.join(format!("{}{}{}", PREFIX, stamp, EXTENSION));
+
+    MaintenanceController::new(&state.pool)
+        .backup_into(&path.to_string_lossy())
+        .await
+        .map_err(|err| err.to_string())?;
+
+    rotate(config).map_err(|err| err.to_string())?;
+
+    Ok(path)
+}
+
+/// Backups sorted from oldest to newest, their names sort by time.
+pub fn list(config: &BackupConfig) -> io::Result<Vec<PathBuf>> {
+    let mut backups = fs::read_dir(&config.dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(PREFIX) && name.ends_with(EXTENSION))
+        })
+        .collect::<Vec<_>>();
+
+    backups.sort();
+
+    Ok(backups)
+}
+
+fn rotate(config: &BackupConfig) -> io::Result<()> {
+    let backups = list(config)?;
+
+    for path in backups
+        .iter()
+        .take(backups.len().saturating_sub(config.keep))
+    {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+pub fn spawn(state: Arc<AppState>, config: BackupConfig) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.interval);
+
+        loop {
+            interval.tick().await;
+
+            match run(&state, &config).await {
+                Ok(path) => tracing::info!(path = %path.display(), "Backed up the database"),
+                Err(err) => tracing::error!(error = %err, "Failed to back up the database"),
+            }
+        }
+    });
+}
@@ -0,0 +1,19 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{controllers::maintenance::MaintenanceController, AppState};
+
+/// Checkpoints the WAL every `interval`. A busy checkpoint is simply retried
+/// on the next run.
+pub fn spawn(state: Arc<AppState>, interval: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = MaintenanceController::new(&state.pool).checkpoint().await {
+                tracing::warn!(error = %err, "Failed to checkpoint the WAL");
+            }
+        }
+    });
+}
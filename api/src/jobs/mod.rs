@@ -0,0 +1,4 @@
+pub mod backup;
+pub mod checkpoint;
+pub mod pregenerate;
+pub mod purge;
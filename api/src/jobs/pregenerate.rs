@@ -0,0 +1,125 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    controllers::{
+        collections::Collection, controller::Controller, questions::QuestionsController,
+        words::WordsController,
+    },
+    llm::{self, prompt::PromptContext, MULTIPLE_CHOICE},
+    AppState,
+};
+
+/// Word, kind, source and target language.
+type PoolKey = (i64, &'static str, String, String);
+
+/// Pools being filled right now. Requests arriving while a pool is filled
+/// leave it alone, instead of counting the same missing questions and paying
+/// for them twice.
+#[derive(Clone, Default)]
+pub struct InFlight(Arc<Mutex<HashSet<PoolKey>>>);
+
+/// Held while filling a pool, releases it when dropped.
+struct Claim {
+    in_flight: InFlight,
+    key: PoolKey,
+}
+
+impl InFlight {
+    fn claim(&self, key: PoolKey) -> Option<Claim> {
+        let claimed = self.0.lock().unwrap().insert(key.clone());
+
+        claimed.then(|| Claim {
+            in_flight: self.clone(),
+            key,
+        })
+    }
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        self.in_flight.0.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Fills the question pool of the given words up to `question_pool_size` in the
+/// background, so serving a question doesn't have to wait for OpenAI.
+pub fn spawn(state: Arc<AppState>, collection: Collection, word_ids: Vec<i64>) {
+    tokio::spawn(async move {
+        let words = WordsController::new(&state.pool);
+        let questions = QuestionsController::new(&state.pool);
+
+        for word_id in word_ids {
+            let word = match words.get_one(word_id).await {
+                Ok(word) => word,
+                Err(err) => {
+                    tracing::warn!(word_id, error = %err, "Skipping question generation");
+                    continue;
+                }
+            };
+
+            let (source_language, target_language) =
+                (&collection.source_language, &collection.target_language);
+
+            let Some(_claim) = state.pregenerating.claim((
+                word.id,
+                MULTIPLE_CHOICE,
+                source_language.clone(),
+                target_language.clone(),
+            )) else {
+                continue;
+            };
+
+            let count = questions
+                .count_for_word(word.id, MULTIPLE_CHOICE, source_language, target_language)
+                .await
+                .unwrap_or(state.question_pool_size);
+
+            let spellings = words.get_spellings(word.id).await.unwrap_or_default();
+
+            for _ in count..state.question_pool_size {
+                // Requests served from the pool don't spend tokens, so keep
+                // the rest of the budget for on-demand generation
+                if !llm::within_budget(&state, None).await.unwrap_or(false) {
+                    return;
+                }
+
+                let context =
+                    PromptContext::new(&word, &spellings, Some(&collection), &state.learner_level);
+
+                let generation = match llm::generate_question(
+                    &state,
+                    MULTIPLE_CHOICE,
+                    &context,
+                    None,
+                )
+                .await
+                {
+                    Ok(generation) => generation,
+                    Err(err) => {
+                        tracing::warn!(word_id = word.id, error = %err, "Failed to generate question");
+                        break;
+                    }
+                };
+
+                let stored = questions
+                    .store(
+                        word.id,
+                        MULTIPLE_CHOICE,
+                        source_language,
+                        target_language,
+                        generation.template_id,
+                        &generation.question,
+                    )
+                    .await;
+
+                if let Err(err) = stored {
+                    tracing::error!(word_id = word.id, error = %err, "Failed to store question");
+                    break;
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,25 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{controllers::trash::TrashController, AppState};
+
+const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Empties the trash of items older than `trash_retention_days` once an hour.
+pub fn spawn(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            match TrashController::new(&state.pool)
+                .purge(state.trash_retention_days)
+                .await
+            {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!(purged, "Purged the trash"),
+                Err(err) => tracing::error!(error = %err, "Failed to purge the trash"),
+            }
+        }
+    });
+}
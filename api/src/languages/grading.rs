@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::controllers::{
+    collections::Collection,
+    words::{Spelling, Word},
+};
+
+use super::{rules, DEFAULT_SOURCE_LANGUAGE, DEFAULT_TARGET_LANGUAGE};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// The learner sees the translation and answers with the word
+    #[default]
+    ToTarget,
+    /// The learner sees the word and answers with the translation
+    ToSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Grade {
+    pub correct: bool,
+    pub expected: String,
+    /// Set to the standard's language code when the answer was spelled in
+    /// another written standard than the one being taught.
+    pub other_standard: Option<String>,
+}
+
+/// Grades an answer against a word. Languages come from the collection the
+/// word is practiced in, or from the word itself outside of a collection.
+pub fn grade(
+    word: &Word,
+    spellings: &[Spelling],
+    collection: Option<&Collection>,
+    direction: Direction,
+    answer: &str,
+) -> Grade {
+    let (source, target) = match collection {
+        Some(collection) => (
+            collection.source_language.as_str(),
+            collection.target_language.as_str(),
+        ),
+        None => (
+            word.source_language
+                .as_deref()
+                .unwrap_or(DEFAULT_SOURCE_LANGUAGE),
+            word.target_language
+                .as_deref()
+                .unwrap_or(DEFAULT_TARGET_LANGUAGE),
+        ),
+    };
+
+    if direction == Direction::ToSource {
+        return Grade {
+            correct: rules(source).grade(&word.translation, answer),
+            expected: word.translation.clone(),
+            other_standard: None,
+        };
+    }
+
+    let expected = word.spelling_for(spellings, target);
+
+    if rules(target).grade(&expected, answer) {
+        return Grade {
+            correct: true,
+            expected,
+            other_standard: None,
+        };
+    }
+
+    // The word itself counts as a spelling in the language it was entered in
+    let own = Spelling {
+        language: word
+            .target_language
+            .clone()
+            .unwrap_or(DEFAULT_TARGET_LANGUAGE.to_string()),
+        spelling: word.word.clone(),
+    };
+
+    let other = spellings
+        .iter()
+        .chain([&own])
+        .filter(|spelling| spelling.language != target)
+        .find(|spelling| rules(&spelling.language).grade(&spelling.spelling, answer));
+
+    match other {
+        Some(other) => Grade {
+            correct: collection.is_none_or(|collection| collection.accept_other_standards),
+            expected,
+            other_standard: Some(other.language.clone()),
+        },
+        None => Grade {
+            correct: false,
+            expected,
+            other_standard: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::controllers::{
+        collections::Collection,
+        words::{Spelling, Word},
+    };
+
+    use super::{grade, Direction};
+
+    /// Bokmål "ikke" with its Nynorsk spelling "ikkje".
+    fn word() -> (Word, Vec<Spelling>) {
+        let word = Word {
+            id: 1,
+            word: "ikke".to_string(),
+            translation: "not".to_string(),
+            image: String::new(),
+            audio: String::new(),
+            source_language: Some("en".to_string()),
+            target_language: Some("nb".to_string()),
+            part_of_speech: None,
+            difficulty: None,
+            version: 1,
+            updated_at: None,
+        };
+        let spellings = vec![Spelling {
+            language: "nn".to_string(),
+            spelling: "ikkje".to_string(),
+        }];
+
+        (word, spellings)
+    }
+
+    fn collection(target_language: &str, accept_other_standards: bool) -> Collection {
+        Collection {
+            id: 1,
+            name: "Basics".to_string(),
+            description: String::new(),
+            source_language: "en".to_string(),
+            target_language: target_language.to_string(),
+            accept_other_standards,
+            filter: None,
+            owner_id: None,
+            visibility: "private".to_string(),
+            cloned_from: None,
+            version: 1,
+            updated_at: None,
+            words: vec![],
+        }
+    }
+
+    #[test]
+    fn grades_both_directions() {
+        let (word, spellings) = word();
+
+        let result = grade(&word, &spellings, None, Direction::ToTarget, "Ikke!");
+        assert!(result.correct);
+        assert_eq!(result.expected, "ikke");
+        assert_eq!(result.other_standard, None);
+
+        let result = grade(&word, &spellings, None, Direction::ToSource, "Not");
+        assert!(result.correct);
+        assert_eq!(result.expected, "not");
+
+        let result = grade(&word, &spellings, None, Direction::ToSource, "ikke");
+        assert!(!result.correct);
+    }
+
+    #[test]
+    fn flags_the_other_standard() {
+        let (word, spellings) = word();
+
+        // Nynorsk in a Bokmål collection
+        let strict = collection("nb", false);
+        let result = grade(
+            &word,
+            &spellings,
+            Some(&strict),
+            Direction::ToTarget,
+            "ikkje",
+        );
+        assert!(!result.correct);
+        assert_eq!(result.expected, "ikke");
+        assert_eq!(result.other_standard.as_deref(), Some("nn"));
+
+        let lenient = collection("nb", true);
+        let result = grade(
+            &word,
+            &spellings,
+            Some(&lenient),
+            Direction::ToTarget,
+            "ikkje",
+        );
+        assert!(result.correct);
+        assert_eq!(result.other_standard.as_deref(), Some("nn"));
+
+        // The word itself is the Bokmål spelling in a Nynorsk collection
+        let nynorsk = collection("nn", false);
+        let result = grade(
+            &word,
+            &spellings,
+            Some(&nynorsk),
+            Direction::ToTarget,
+            "ikke",
+        );
+        assert!(!result.correct);
+        assert_eq!(result.expected, "ikkje");
+        assert_eq!(result.other_standard.as_deref(), Some("nb"));
+
+        // Outside of a collection other standards count
+        let result = grade(&word, &spellings, None, Direction::ToTarget, "ikkje");
+        assert!(result.correct);
+        assert_eq!(result.other_standard.as_deref(), Some("nn"));
+
+        let result = grade(&word, &spellings, Some(&strict), Direction::ToTarget, "ja");
+        assert!(!result.correct);
+        assert_eq!(result.other_standard, None);
+    }
+}
@@ -0,0 +1,196 @@
+pub mod grading;
+
+pub const DEFAULT_SOURCE_LANGUAGE: &str = "en";
+pub const DEFAULT_TARGET_LANGUAGE: &str = "no";
+
+/// Rules for comparing answers in one language, looked up by BCP-47 code.
+pub struct Language {
+    pub code: &'static str,
+    pub name: &'static str,
+    /// Leading words dropped before comparing, e.g. articles or infinitive markers
+    pub articles: &'static [&'static str],
+    /// Characters replaced before comparing, e.g. accents learners tend to skip
+    pub folds: &'static [(char, &'static str)],
+    /// Question asking for the word behind a translation, which takes the place of `{}`
+    pub which_word: &'static str,
+}
+
+static LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        name: "English",
+        articles: &["the", "a", "an", "to"],
+        folds: &[],
+        which_word: "Which word means '{}'?",
+    },
+    Language {
+        code: "no",
+        name: "Norwegian",
+        articles: &["en", "ei", "et", "å"],
+        folds: &[],
+        which_word: "Hvilket ord betyr '{}'?",
+    },
+    Language {
+        code: "nb",
+        name: "Norwegian Bokmål",
+        articles: &["en", "ei", "et", "å"],
+        folds: &[],
+        which_word: "Hvilket ord betyr '{}'?",
+    },
+    Language {
+        code: "nn",
+        name: "Norwegian Nynorsk",
+        articles: &["ein", "ei", "eit", "å"],
+        folds: &[],
+        which_word: "Kva ord tyder '{}'?",
+    },
+    Language {
+        code: "de",
+        name: "German",
+        articles: &[
+            "der", "die", "das", "den", "dem", "des", "ein", "eine", "einen", "einem", "einer",
+            "eines",
+        ],
+        folds: &[('ä', "a"), ('ö', "o"), ('ü', "u"), ('ß', "ss")],
+        which_word: "Welches Wort bedeutet '{}'?",
+    },
+    Language {
+        code: "es",
+        name: "Spanish",
+        articles: &["el", "la", "los", "las", "un", "una", "unos", "unas"],
+        folds: &[
+            ('á', "a"),
+            ('é', "e"),
+            ('í', "i"),
+            ('ó', "o"),
+            ('ú', "u"),
+            ('ü', "u"),
+        ],
+        which_word: "¿Qué palabra significa '{}'?",
+    },
+];
+
+static GENERIC: Language = Language {
+    code: "und",
+    name: "Unknown",
+    articles: &[],
+    folds: &[],
+    which_word: "Which word means '{}'?",
+};
+
+/// Finds the rules for a BCP-47 code, falling back to its primary subtag so
+/// `de-AT` uses the rules of `de`.
+pub fn get(code: &str) -> Option<&'static Language> {
+    let code = code.to_lowercase();
+    let primary = code.split(['-', '_']).next().unwrap_or_default();
+
+    LANGUAGES
+        .iter()
+        .find(|language| language.code == code)
+        .or_else(|| LANGUAGES.iter().find(|language| language.code == primary))
+}
+
+/// Human readable name of a language, or the code itself if it is unknown.
+pub fn name(code: &str) -> String {
+    get(code)
+        .map(|language| language.name.to_string())
+        .unwrap_or(code.to_string())
+}
+
+impl Language {
+    pub fn normalize(&self, text: &str) -> String {
+        let mut text = text
+            .to_lowercase()
+            .chars()
+            .filter(|c| !matches!(c, '.' | ',' | '!' | '?' | ';' | ':' | '"' | '\''))
+            .map(|c| {
+                self.folds
+                    .iter()
+                    .find(|(from, _)| *from == c)
+                    .map(|(_, to)| to.to_string())
+                    .unwrap_or(c.to_string())
+            })
+            .collect::<String>();
+
+        let words = text.split_whitespace().collect::<Vec<_>>();
+
+        if words.len() > 1 && self.articles.contains(&words[0]) {
+            text = words[1..].join(" ");
+        } else {
+            text = words.join(" ");
+        }
+
+        text
+    }
+
+    /// Whether `answer` matches `expected`, which may list several accepted
+    /// alternatives separated by `,`, `;` or `/`.
+    pub fn grade(&self, expected: &str, answer: &str) -> bool {
+        let answer = self.normalize(answer);
+
+        !answer.is_empty()
+            && expected
+                .split([',', ';', '/'])
+                .any(|alternative| self.normalize(alternative) == answer)
+    }
+}
+
+/// Rules for a code, or language independent ones if the code is unknown.
+pub fn rules(code: &str) -> &'static Language {
+    get(code).unwrap_or(&GENERIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get, name, rules};
+
+    #[test]
+    fn looks_up_codes_by_primary_subtag() {
+        assert_eq!(get("de-AT").unwrap().code, "de");
+        assert_eq!(get("NB").unwrap().code, "nb");
+        assert_eq!(get("nn_NO").unwrap().code, "nn");
+        assert!(get("fi").is_none());
+        assert_eq!(rules("fi").code, "und");
+        assert_eq!(name("es"), "Spanish");
+        assert_eq!(name("fi"), "fi");
+    }
+
+    #[test]
+    fn normalizes_per_language() {
+        for (code, text, normalized) in [
+            ("en", "The House!", "house"),
+            ("en", "to eat", "eat"),
+            ("en", "a", "a"),
+            ("no", "en bil", "bil"),
+            ("no", "å spise", "spise"),
+            ("nb", "ei jente", "jente"),
+            ("nb", "et hus", "hus"),
+            ("nn", "eit hus", "hus"),
+            ("nn", "ein bil", "bil"),
+            // Bokmål articles aren't Nynorsk ones
+            ("nn", "et hus", "et hus"),
+            ("de", "der Hund", "hund"),
+            ("de", "die Brücke", "brucke"),
+            ("de", "Straße", "strasse"),
+            ("es", "la canción", "cancion"),
+            ("es", "los pingüinos", "pinguinos"),
+            ("es", "el niño", "niño"),
+            ("fi", "  kissa  on ", "kissa on"),
+        ] {
+            assert_eq!(rules(code).normalize(text), normalized, "{} {}", code, text);
+        }
+    }
+
+    #[test]
+    fn grades_against_each_alternative() {
+        let english = rules("en");
+
+        assert!(english.grade("house, home / building", "Home"));
+        assert!(english.grade("house, home / building", "a building"));
+        assert!(!english.grade("house, home / building", "hut"));
+        assert!(!english.grade("house", ""));
+        assert!(!english.grade("house", "?"));
+        assert!(rules("de").grade("das Mädchen", "madchen"));
+        assert!(!rules("en").grade("Mädchen", "madchen"));
+    }
+}
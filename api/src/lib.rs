@@ -0,0 +1,40 @@
+use jobs::{backup::BackupConfig, pregenerate::InFlight};
+use metrics::Metrics;
+use reqwest::Client;
+use sqlx::{migrate::Migrator, SqlitePool};
+
+pub mod cli;
+pub mod codegen;
+pub mod controllers;
+pub mod endpoints;
+pub mod jobs;
+pub mod languages;
+pub mod llm;
+pub mod metrics;
+pub mod routes;
+pub mod telemetry;
+pub mod util;
+
+/// The migrations in `migrations/`, embedded into the binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: SqlitePool,
+    pub http_client: Client,
+    /// Chat completions endpoint, OpenAI's unless `OPENAI_API_URL` is set
+    pub llm_url: String,
+    pub question_pool_size: i64,
+    pub question_retries: u32,
+    pub learner_level: String,
+    pub daily_token_budget: Option<i64>,
+    pub user_daily_token_budget: Option<i64>,
+    pub prompt_token_price: f64,
+    pub completion_token_price: f64,
+    pub trash_retention_days: i64,
+    pub admin_users: Vec<String>,
+    pub backup: Option<BackupConfig>,
+    pub metrics: Metrics,
+    /// Question pools currently filled in the background
+    pub pregenerating: InFlight,
+}
@@ -0,0 +1,316 @@
+use std::{fmt, time::Instant};
+
+use serde_json::{json, Value};
+use tracing::Instrument;
+
+use crate::{
+    controllers::{
+        llm_calls::{LlmCallsController, NewLlmCall},
+        prompt_templates::PromptTemplatesController,
+        questions::Question,
+    },
+    telemetry::redact,
+    AppState,
+};
+
+use self::{
+    prompt::{render, PromptContext, RenderedPrompt},
+    validate::{validate, Violation},
+};
+
+pub mod offline;
+pub mod prompt;
+pub mod validate;
+
+pub const MULTIPLE_CHOICE: &str = "multiple_choice";
+
+const MODEL: &str = "gpt-4o-2024-08-06";
+
+pub const OPENAI_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+#[derive(Debug)]
+pub enum LlmError {
+    Database(sqlx::Error),
+    Template(minijinja::Error),
+    Request(reqwest::Error),
+    Provider(String),
+    MalformedResponse(String),
+    Invalid(Vec<Violation>),
+}
+
+impl fmt::Display for LlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LlmError::Database(err) => write!(f, "failed to load prompt template: {}", err),
+            LlmError::Template(err) => write!(f, "failed to render prompt template: {}", err),
+            LlmError::Request(err) => write!(f, "request to OpenAI failed: {}", err),
+            LlmError::Provider(message) => write!(f, "OpenAI returned an error: {}", message),
+            LlmError::MalformedResponse(message) => {
+                write!(f, "OpenAI returned a malformed response: {}", message)
+            }
+            LlmError::Invalid(violations) => write!(
+                f,
+                "generated question is still invalid after retrying: {}",
+                violations
+                    .iter()
+                    .map(Violation::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+        }
+    }
+}
+
+impl LlmError {
+    /// Outcome stored in `llm_calls` for a call that ended in this error.
+    fn outcome(&self) -> &'static str {
+        match self {
+            LlmError::Database(_) | LlmError::Template(_) => "internal_error",
+            LlmError::Request(_) => "request_error",
+            LlmError::Provider(_) => "provider_error",
+            LlmError::MalformedResponse(_) => "malformed_response",
+            LlmError::Invalid(_) => "invalid_question",
+        }
+    }
+}
+
+impl From<sqlx::Error> for LlmError {
+    fn from(err: sqlx::Error) -> Self {
+        LlmError::Database(err)
+    }
+}
+
+impl From<minijinja::Error> for LlmError {
+    fn from(err: minijinja::Error) -> Self {
+        LlmError::Template(err)
+    }
+}
+
+impl From<reqwest::Error> for LlmError {
+    fn from(err: reqwest::Error) -> Self {
+        // The URL may carry a key for providers that take it as a parameter
+        LlmError::Request(err.without_url())
+    }
+}
+
+pub struct Generation {
+    pub question: Question,
+    pub template_id: i64,
+}
+
+struct Completion {
+    content: String,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+}
+
+/// Whether the daily token budgets still allow a call on behalf of `user_id`.
+/// Calls without a user only count against the global budget.
+pub async fn within_budget(state: &AppState, user_id: Option<i64>) -> Result<bool, sqlx::Error> {
+    let calls = LlmCallsController::new(&state.pool);
+
+    if let Some(budget) = state.daily_token_budget {
+        if calls.tokens_today(None).await? >= budget {
+            return Ok(false);
+        }
+    }
+
+    if let (Some(budget), Some(user_id)) = (state.user_daily_token_budget, user_id) {
+        if calls.tokens_today(Some(user_id)).await? >= budget {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Asks OpenAI for a question of the given kind, using the latest prompt template
+/// of that name. Answers that can't be parsed or break the rules in [`validate`]
+/// are sent back with a corrective message, up to `question_retries` times.
+#[tracing::instrument(skip_all, fields(kind = kind, word_id = context.word.id))]
+pub async fn generate_question(
+    state: &AppState,
+    kind: &str,
+    context: &PromptContext<'_>,
+    user_id: Option<i64>,
+) -> Result<Generation, LlmError> {
+    let template = PromptTemplatesController::new(&state.pool)
+        .get_latest(kind)
+        .await?;
+
+    let prompt = render(&template, context)?;
+
+    let mut messages = vec![
+        json!({
+            "role": "system",
+            "content": [{ "type": "text", "text": prompt.system }]
+        }),
+        json!({
+            "role": "user",
+            "content": [{ "type": "text", "text": prompt.user }]
+        }),
+    ];
+
+    let mut attempt = 0;
+
+    loop {
+        let started = Instant::now();
+        let completion = request_completion(state, &prompt, &messages)
+            .instrument(tracing::info_span!("llm_call", model = MODEL, attempt))
+            .await;
+        let latency_ms = started.elapsed().as_millis() as i64;
+
+        let mut call = NewLlmCall {
+            user_id,
+            model: MODEL,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            latency_ms,
+            outcome: "success",
+        };
+
+        let completion = match completion {
+            Ok(completion) => completion,
+            Err(err) => {
+                call.outcome = err.outcome();
+                record_call(state, &call).await;
+                return Err(err);
+            }
+        };
+
+        call.prompt_tokens = completion.prompt_tokens;
+        call.completion_tokens = completion.completion_tokens;
+
+        let content = completion.content;
+
+        let error = match serde_json::from_str::<Question>(&content) {
+            Ok(question) => match validate(&question, context) {
+                Ok(()) => {
+                    record_call(state, &call).await;
+
+                    return Ok(Generation {
+                        question,
+                        template_id: prompt.template_id,
+                    });
+                }
+                Err(violations) => LlmError::Invalid(violations),
+            },
+            Err(err) => LlmError::MalformedResponse(err.to_string()),
+        };
+
+        call.outcome = error.outcome();
+        record_call(state, &call).await;
+
+        if attempt >= state.question_retries {
+            return Err(error);
+        }
+
+        attempt += 1;
+
+        let correction = match &error {
+            LlmError::Invalid(violations) => violations
+                .iter()
+                .map(Violation::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => "The answer is not valid JSON for the given schema.".to_string(),
+        };
+
+        messages.push(json!({
+            "role": "assistant",
+            "content": [{ "type": "text", "text": content }]
+        }));
+        messages.push(json!({
+            "role": "user",
+            "content": [{
+                "type": "text",
+                "text": format!("Your question breaks the rules: {} Please send a corrected question.", correction)
+            }]
+        }));
+    }
+}
+
+async fn request_completion(
+    state: &AppState,
+    prompt: &RenderedPrompt,
+    messages: &[Value],
+) -> Result<Completion, LlmError> {
+    let body = json!({
+        "model": MODEL,
+        "messages": messages,
+        "temperature": 1.52,
+        "max_tokens": 2048,
+        "top_p": 1,
+        "frequency_penalty": 0.61,
+        "presence_penalty": 0,
+        "response_format": {
+            "type": "json_schema",
+            "json_schema": {
+                "name": "response",
+                "strict": true,
+                "schema": prompt.schema
+            }
+        }
+    });
+
+    let response = state
+        .http_client
+        .post(&state.llm_url)
+        .body(body.to_string())
+        .send()
+        .await?
+        .text()
+        .await?;
+
+    let response: Value = serde_json::from_str(&response)
+        .map_err(|err| LlmError::MalformedResponse(err.to_string()))?;
+
+    if let Some(message) = response.pointer("/error/message").and_then(Value::as_str) {
+        return Err(LlmError::Provider(redact(message)));
+    }
+
+    let content = response
+        .pointer("/choices/0/message/content")
+        .and_then(Value::as_str)
+        .ok_or_else(|| LlmError::MalformedResponse("missing choices[0].message.content".into()))?;
+
+    let tokens = |key: &str| {
+        response
+            .pointer(&format!("/usage/{}", key))
+            .and_then(Value::as_i64)
+            .unwrap_or(0)
+    };
+
+    Ok(Completion {
+        content: content.to_string(),
+        prompt_tokens: tokens("prompt_tokens"),
+        completion_tokens: tokens("completion_tokens"),
+    })
+}
+
+async fn record_call(state: &AppState, call: &NewLlmCall<'_>) {
+    state.metrics.record_llm_call(call);
+
+    match call.outcome {
+        "success" => tracing::info!(
+            model = call.model,
+            outcome = call.outcome,
+            latency_ms = call.latency_ms,
+            prompt_tokens = call.prompt_tokens,
+            completion_tokens = call.completion_tokens,
+            "LLM call"
+        ),
+        _ => tracing::warn!(
+            model = call.model,
+            outcome = call.outcome,
+            latency_ms = call.latency_ms,
+            prompt_tokens = call.prompt_tokens,
+            completion_tokens = call.completion_tokens,
+            "LLM call failed"
+        ),
+    }
+
+    if let Err(err) = LlmCallsController::new(&state.pool).record(call).await {
+        tracing::error!(error = %err, "Failed to record LLM call");
+    }
+}
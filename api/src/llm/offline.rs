@@ -0,0 +1,59 @@
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+
+use crate::{
+    controllers::{
+        questions::{Option, Question},
+        words::Word,
+    },
+    languages,
+};
+
+use super::{
+    prompt::PromptContext,
+    validate::{self, Violation, MAX_OPTIONS},
+};
+
+/// Builds a question from the collection itself when OpenAI can't be used, by
+/// asking for the word behind a translation among some of its neighbours.
+/// Distractors are taken in order, skipping any written like an option already
+/// taken. The question is held to the same rules as generated ones, so
+/// collections with too few distinct words get none.
+pub fn generate_question(
+    context: &PromptContext,
+    distractors: &[Word],
+) -> Result<Question, Vec<Violation>> {
+    let target = languages::rules(&context.target_language_code);
+    let source = languages::rules(&context.source_language_code);
+
+    let mut seen = HashSet::from([target.normalize(&context.spelling)]);
+    let mut options = vec![Option {
+        name: context.spelling.clone(),
+        correct: true,
+    }];
+
+    for distractor in distractors {
+        if options.len() == MAX_OPTIONS {
+            break;
+        }
+
+        if seen.insert(target.normalize(&distractor.word)) {
+            options.push(Option {
+                name: distractor.word.clone(),
+                correct: false,
+            });
+        }
+    }
+
+    options.shuffle(&mut rand::thread_rng());
+
+    let question = Question {
+        question: source.which_word.replace("{}", &context.word.translation),
+        options,
+    };
+
+    validate::validate(&question, context)?;
+
+    Ok(question)
+}
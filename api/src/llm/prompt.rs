@@ -0,0 +1,96 @@
+use minijinja::{Environment, UndefinedBehavior};
+use serde::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::{
+    controllers::{
+        collections::Collection,
+        prompt_templates::PromptTemplate,
+        words::{Spelling, Word},
+    },
+    languages::{self, DEFAULT_SOURCE_LANGUAGE, DEFAULT_TARGET_LANGUAGE},
+};
+
+/// Variables available inside a prompt template, e.g. `{{ word.translation }}`
+/// or `{{ collection.name }}`. Languages are given as names, their BCP-47 codes
+/// are available as `source_language_code` and `target_language_code`.
+/// `spelling` is the word as written in the target language's standard.
+#[derive(Serialize, Clone, Debug)]
+pub struct PromptContext<'a> {
+    pub word: &'a Word,
+    pub spelling: String,
+    pub spellings: &'a [Spelling],
+    pub collection: Option<&'a Collection>,
+    pub level: &'a str,
+    pub source_language: String,
+    pub source_language_code: String,
+    pub target_language: String,
+    pub target_language_code: String,
+}
+
+impl<'a> PromptContext<'a> {
+    /// Takes the languages of the collection, or the ones the word inherited
+    /// when there is no collection at hand.
+    pub fn new(
+        word: &'a Word,
+        spellings: &'a [Spelling],
+        collection: Option<&'a Collection>,
+        level: &'a str,
+    ) -> Self {
+        let (source, target) = match collection {
+            Some(collection) => (
+                collection.source_language.clone(),
+                collection.target_language.clone(),
+            ),
+            None => (
+                word.source_language
+                    .clone()
+                    .unwrap_or(DEFAULT_SOURCE_LANGUAGE.to_string()),
+                word.target_language
+                    .clone()
+                    .unwrap_or(DEFAULT_TARGET_LANGUAGE.to_string()),
+            ),
+        };
+
+        PromptContext {
+            word,
+            spelling: word.spelling_for(spellings, &target),
+            spellings,
+            collection,
+            level,
+            source_language: languages::name(&source),
+            source_language_code: source,
+            target_language: languages::name(&target),
+            target_language_code: target,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct RenderedPrompt {
+    pub template_id: i64,
+    pub name: String,
+    pub version: i64,
+    pub system: String,
+    pub user: String,
+    pub schema: Value,
+}
+
+pub fn render(
+    template: &PromptTemplate,
+    context: &PromptContext,
+) -> Result<RenderedPrompt, minijinja::Error> {
+    let mut env = Environment::new();
+    // Fail on typos like `{{ word.wrod }}` instead of silently rendering nothing
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+
+    Ok(RenderedPrompt {
+        template_id: template.id,
+        name: template.name.clone(),
+        version: template.version,
+        system: env.render_str(&template.system_prompt, context)?,
+        user: env.render_str(&template.user_prompt, context)?,
+        schema: template.response_schema.0.clone(),
+    })
+}
@@ -0,0 +1,172 @@
+use std::{collections::HashSet, fmt};
+
+use serde::Serialize;
+
+use crate::{controllers::questions::Question, languages};
+
+use super::prompt::PromptContext;
+
+pub const MIN_OPTIONS: usize = 3;
+pub const MAX_OPTIONS: usize = 5;
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "rule", content = "option", rename_all = "snake_case")]
+pub enum Violation {
+    OptionCount(usize),
+    NoCorrectOption,
+    DuplicateOption(String),
+    NotSingleWord(String),
+    Translation(String),
+    OtherStandard(String),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::OptionCount(count) => write!(
+                f,
+                "There are {} options, but there must be between {} and {}.",
+                count, MIN_OPTIONS, MAX_OPTIONS
+            ),
+            Violation::NoCorrectOption => write!(f, "At least one option must be correct."),
+            Violation::DuplicateOption(name) => {
+                write!(f, "The option '{}' appears more than once.", name)
+            }
+            Violation::NotSingleWord(name) => {
+                write!(f, "The option '{}' is not a single word.", name)
+            }
+            Violation::Translation(name) => write!(
+                f,
+                "The option '{}' is the translation of the word, don't ask for it.",
+                name
+            ),
+            Violation::OtherStandard(name) => write!(
+                f,
+                "The option '{}' is spelled in another written standard, only use the target standard.",
+                name
+            ),
+        }
+    }
+}
+
+/// Checks a generated question against the rules given in the prompt, since
+/// the model doesn't reliably follow them.
+pub fn validate(question: &Question, context: &PromptContext) -> Result<(), Vec<Violation>> {
+    let mut violations = vec![];
+
+    let count = question.options.len();
+
+    if !(MIN_OPTIONS..=MAX_OPTIONS).contains(&count) {
+        violations.push(Violation::OptionCount(count));
+    }
+
+    if !question.options.iter().any(|option| option.correct) {
+        violations.push(Violation::NoCorrectOption);
+    }
+
+    let target = languages::rules(&context.target_language_code);
+    let source = languages::rules(&context.source_language_code);
+
+    // Spellings of the word in other standards, unless they are written the same
+    let other_standards = context
+        .spellings
+        .iter()
+        .filter(|spelling| spelling.language != context.target_language_code)
+        .map(|spelling| target.normalize(&spelling.spelling))
+        .filter(|spelling| *spelling != target.normalize(&context.spelling))
+        .collect::<Vec<_>>();
+
+    let mut seen = HashSet::new();
+
+    for option in &question.options {
+        let name = target.normalize(&option.name);
+
+        if !seen.insert(name.clone()) {
+            violations.push(Violation::DuplicateOption(option.name.clone()));
+        }
+
+        // On the name as shown, since normalizing drops leading articles
+        if name.is_empty() || option.name.split_whitespace().count() > 1 {
+            violations.push(Violation::NotSingleWord(option.name.clone()));
+        }
+
+        if source.grade(&context.word.translation, &option.name) {
+            violations.push(Violation::Translation(option.name.clone()));
+        }
+
+        if other_standards.contains(&name) {
+            violations.push(Violation::OtherStandard(option.name.clone()));
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::controllers::{
+        questions::{Option, Question},
+        words::Word,
+    };
+
+    use super::{validate, PromptContext, Violation};
+
+    fn word() -> Word {
+        Word {
+            id: 1,
+            word: "bil".to_string(),
+            translation: "car".to_string(),
+            image: String::new(),
+            audio: String::new(),
+            source_language: Some("en".to_string()),
+            target_language: Some("no".to_string()),
+            part_of_speech: None,
+            difficulty: None,
+            version: 1,
+            updated_at: None,
+        }
+    }
+
+    fn question(names: &[&str]) -> Question {
+        Question {
+            question: "Which of these has wheels?".to_string(),
+            options: names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| Option {
+                    name: name.to_string(),
+                    correct: i == 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_single_words() {
+        let word = word();
+        let context = PromptContext::new(&word, &[], None, "beginner");
+
+        assert_eq!(
+            validate(&question(&["bil", "hus", "eple"]), &context),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn refuses_options_with_articles() {
+        let word = word();
+        let context = PromptContext::new(&word, &[], None, "beginner");
+
+        assert_eq!(
+            validate(&question(&["en bil", "hus", "å spise"]), &context),
+            Err(vec![
+                Violation::NotSingleWord("en bil".to_string()),
+                Violation::NotSingleWord("å spise".to_string()),
+            ])
+        );
+    }
+}
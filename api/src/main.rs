@@ -1,26 +1,27 @@
-use std::{env, sync::Arc};
+use std::{env, sync::Arc, time::Duration};
 
 use dotenv::dotenv;
+use drue::{
+    cli, jobs, jobs::backup::BackupConfig, llm, metrics::Metrics, routes::create_router, telemetry,
+    AppState,
+};
 use reqwest::{header, Client};
-use routes::create_router;
 use sqlx::SqlitePool;
 use tokio::net::TcpListener;
 
-mod controllers;
-mod endpoints;
-mod routes;
-mod util;
-
-#[derive(Clone)]
-pub struct AppState {
-    pool: SqlitePool,
-    http_client: Client,
-}
-
 #[tokio::main]
 async fn main() {
     dotenv().ok();
 
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args).await);
+    }
+
+    // `pretty` or `json`
+    telemetry::init(&env::var("LOG_FORMAT").unwrap_or("pretty".to_string()));
+
     let pool = SqlitePool::connect(&env::var("DATABASE_URL").unwrap())
         .await
         .unwrap();
@@ -38,16 +39,102 @@ async fn main() {
 
     let client = Client::builder().default_headers(headers).build().unwrap();
 
+    let llm_url = env::var("OPENAI_API_URL").unwrap_or(llm::OPENAI_URL.to_string());
+
+    let question_pool_size = env::var("QUESTION_POOL_SIZE")
+        .map(|size| size.parse().unwrap())
+        .unwrap_or(5);
+
+    let question_retries = env::var("QUESTION_RETRIES")
+        .map(|retries| retries.parse().unwrap())
+        .unwrap_or(2);
+
+    let learner_level = env::var("LEARNER_LEVEL").unwrap_or("beginner".to_string());
+
+    let daily_token_budget = env::var("LLM_DAILY_TOKEN_BUDGET")
+        .ok()
+        .map(|budget| budget.parse().unwrap());
+
+    let user_daily_token_budget = env::var("LLM_USER_DAILY_TOKEN_BUDGET")
+        .ok()
+        .map(|budget| budget.parse().unwrap());
+
+    // USD per million tokens, defaults to the prices of gpt-4o-2024-08-06
+    let prompt_token_price = env::var("LLM_PROMPT_TOKEN_PRICE")
+        .map(|price| price.parse().unwrap())
+        .unwrap_or(2.5);
+
+    let completion_token_price = env::var("LLM_COMPLETION_TOKEN_PRICE")
+        .map(|price| price.parse().unwrap())
+        .unwrap_or(10.0);
+
+    let trash_retention_days = env::var("TRASH_RETENTION_DAYS")
+        .map(|days| days.parse().unwrap())
+        .unwrap_or(30);
+
+    // Comma separated names of the users allowed to export and import
+    let admin_users = env::var("ADMIN_USERS")
+        .map(|names| {
+            names
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Backups are only taken when a directory is configured
+    let backup = env::var("BACKUP_DIR").ok().map(|dir| BackupConfig {
+        dir: dir.into(),
+        interval: Duration::from_secs(
+            env::var("BACKUP_INTERVAL_HOURS")
+                .map(|hours| hours.parse().unwrap())
+                .unwrap_or(24)
+                * 60
+                * 60,
+        ),
+        keep: env::var("BACKUP_KEEP")
+            .map(|keep| keep.parse().unwrap())
+            .unwrap_or(7),
+    });
+
+    let checkpoint_interval = Duration::from_secs(
+        env::var("WAL_CHECKPOINT_MINUTES")
+            .map(|minutes| minutes.parse().unwrap())
+            .unwrap_or(15)
+            * 60,
+    );
+
     let shared_state = Arc::new(AppState {
         pool,
         http_client: client,
+        llm_url,
+        question_pool_size,
+        question_retries,
+        learner_level,
+        daily_token_budget,
+        user_daily_token_budget,
+        prompt_token_price,
+        completion_token_price,
+        trash_retention_days,
+        admin_users,
+        backup: backup.clone(),
+        metrics: Metrics::new(),
+        pregenerating: Default::default(),
     });
 
+    jobs::purge::spawn(shared_state.clone());
+    jobs::checkpoint::spawn(shared_state.clone(), checkpoint_interval);
+
+    if let Some(backup) = backup {
+        jobs::backup::spawn(shared_state.clone(), backup);
+    }
+
     let app = create_router(shared_state);
 
     let listener = TcpListener::bind(&env::var("URL").unwrap()).await.unwrap();
 
-    println!("Listening on {}", listener.local_addr().unwrap());
+    tracing::info!("Listening on {}", listener.local_addr().unwrap());
 
     axum::serve(listener, app).await.unwrap();
 }
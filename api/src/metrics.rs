@@ -0,0 +1,223 @@
+//! Prometheus metrics, served at `/metrics`. Counters and histograms are
+//! updated as requests and LLM calls finish, gauges are read when scraped.
+//! Every app has its own registry, so tests can run several side by side.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use sqlx::SqlitePool;
+
+use crate::{
+    controllers::{llm_calls::NewLlmCall, reviews::ReviewsController},
+    AppState,
+};
+
+/// How recently someone must have answered to count as practicing.
+pub const ACTIVE_LEARNER_MINUTES: i64 = 15;
+
+pub const CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+
+/// Where a served question came from: the pre-generated pool, an LLM call
+/// while the learner waited, or the offline fallback once the token budget is
+/// spent. The pool's share of the total is the cache hit ratio.
+pub const FROM_POOL: &str = "pool";
+pub const GENERATED: &str = "generated";
+pub const OFFLINE: &str = "offline";
+
+const LLM_LATENCY_BUCKETS: [f64; 9] = [0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 40.0, 80.0];
+
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    llm_calls: IntCounterVec,
+    llm_call_duration: HistogramVec,
+    llm_tokens: IntCounterVec,
+    questions_served: IntCounterVec,
+    pool_connections: IntGaugeVec,
+    pool_max_connections: IntGauge,
+    active_learners: IntGauge,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("drue".into()), None).unwrap();
+
+        let metrics = Metrics {
+            http_requests: IntCounterVec::new(
+                Opts::new("http_requests_total", "HTTP requests handled"),
+                &["method", "route", "status"],
+            )
+            .unwrap(),
+            http_request_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "http_request_duration_seconds",
+                    "Time taken to answer HTTP requests",
+                ),
+                &["method", "route", "status"],
+            )
+            .unwrap(),
+            llm_calls: IntCounterVec::new(
+                Opts::new(
+                    "llm_calls_total",
+                    "LLM calls by outcome, anything but success is a failure",
+                ),
+                &["model", "outcome"],
+            )
+            .unwrap(),
+            llm_call_duration: HistogramVec::new(
+                HistogramOpts::new("llm_call_duration_seconds", "Time taken by LLM calls")
+                    .buckets(LLM_LATENCY_BUCKETS.to_vec()),
+                &["model", "outcome"],
+            )
+            .unwrap(),
+            llm_tokens: IntCounterVec::new(
+                Opts::new("llm_tokens_total", "Tokens used by LLM calls"),
+                &["model", "kind"],
+            )
+            .unwrap(),
+            questions_served: IntCounterVec::new(
+                Opts::new("questions_served_total", "Questions served by source"),
+                &["source"],
+            )
+            .unwrap(),
+            pool_connections: IntGaugeVec::new(
+                Opts::new("db_pool_connections", "Open database connections"),
+                &["state"],
+            )
+            .unwrap(),
+            pool_max_connections: IntGauge::new(
+                "db_pool_max_connections",
+                "Most database connections the pool opens",
+            )
+            .unwrap(),
+            active_learners: IntGauge::new(
+                "active_learners",
+                "Signed in users who answered within the last 15 minutes",
+            )
+            .unwrap(),
+            registry,
+        };
+
+        metrics.register();
+
+        metrics
+    }
+
+    fn register(&self) {
+        let collectors: [Box<dyn prometheus::core::Collector>; 9] = [
+            Box::new(self.http_requests.clone()),
+            Box::new(self.http_request_duration.clone()),
+            Box::new(self.llm_calls.clone()),
+            Box::new(self.llm_call_duration.clone()),
+            Box::new(self.llm_tokens.clone()),
+            Box::new(self.questions_served.clone()),
+            Box::new(self.pool_connections.clone()),
+            Box::new(self.pool_max_connections.clone()),
+            Box::new(self.active_learners.clone()),
+        ];
+
+        for collector in collectors {
+            self.registry.register(collector).unwrap();
+        }
+    }
+
+    pub fn record_request(&self, method: &str, route: &str, status: u16, latency: Duration) {
+        let status = status.to_string();
+        let labels = [method, route, status.as_str()];
+
+        self.http_requests.with_label_values(&labels).inc();
+        self.http_request_duration
+            .with_label_values(&labels)
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn record_llm_call(&self, call: &NewLlmCall<'_>) {
+        let labels = [call.model, call.outcome];
+
+        self.llm_calls.with_label_values(&labels).inc();
+        self.llm_call_duration
+            .with_label_values(&labels)
+            .observe(call.latency_ms as f64 / 1000.0);
+
+        for (kind, tokens) in [
+            ("prompt", call.prompt_tokens),
+            ("completion", call.completion_tokens),
+        ] {
+            self.llm_tokens
+                .with_label_values(&[call.model, kind])
+                .inc_by(tokens.max(0) as u64);
+        }
+    }
+
+    /// `source` is one of [`FROM_POOL`], [`GENERATED`] or [`OFFLINE`].
+    pub fn record_question(&self, source: &str) {
+        self.questions_served.with_label_values(&[source]).inc();
+    }
+
+    /// Reads the gauges and renders everything in the Prometheus text format.
+    pub async fn render(&self, pool: &SqlitePool) -> Result<String, sqlx::Error> {
+        let idle = pool.num_idle() as i64;
+
+        self.pool_connections.with_label_values(&["idle"]).set(idle);
+        self.pool_connections
+            .with_label_values(&["in_use"])
+            .set(pool.size() as i64 - idle);
+        self.pool_max_connections
+            .set(pool.options().get_max_connections() as i64);
+
+        self.active_learners.set(
+            ReviewsController::new(pool)
+                .count_active_learners(ACTIVE_LEARNER_MINUTES)
+                .await?,
+        );
+
+        Ok(TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .unwrap())
+    }
+}
+
+/// Counts every request by method, matched route and status. Like the request
+/// spans, it never sees the raw path.
+pub async fn track_requests(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or("unmatched".to_string());
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    state.metrics.record_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+
+    response
+}
@@ -1,17 +1,46 @@
 use std::sync::Arc;
 
-use axum::{routing::get, Router};
-use tower_http::services::ServeDir;
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, post, put},
+    Router,
+};
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    services::ServeDir,
+    trace::TraceLayer,
+};
 
 use crate::{
     endpoints::{
         collections::{
-            delete_collection, get_all_collections, get_one_collection, post_collection,
-            put_collection,
+            clone_collection, delete_collection, discover_collections, get_all_collections,
+            get_one_collection, post_collection, put_collection,
+        },
+        docs::{get_docs, get_openapi},
+        dump::{get_export, post_import, IMPORT_BODY_LIMIT},
+        health::{get_healthz, get_readyz},
+        llm_calls::get_llm_stats,
+        maintenance::{get_integrity, post_backup, post_checkpoint},
+        members::{
+            accept_invite, delete_member, get_activity, get_members, post_invite, put_member,
+        },
+        metrics::get_metrics,
+        prompt_templates::{
+            get_all_prompt_templates, get_one_prompt_template, post_prompt_template,
+            preview_prompt_template,
+        },
+        tags::{delete_tag, get_all_tags, get_one_tag, post_tag, put_tag},
+        trash::{get_trash, restore_from_trash},
+        users::{get_me, post_session, post_user},
+        words::{
+            delete_word, generate_question, get_all_words, get_duplicate_words, get_one_word,
+            get_spellings, get_word_reviews, get_word_revisions, get_word_tags, post_answer,
+            post_merge, post_word, put_spellings, put_word, put_word_tags, revert_word,
         },
-        words::{delete_word, generate_question, get_all_words, get_one_word, post_word, put_word},
     },
-    AppState,
+    metrics, telemetry, AppState,
 };
 
 pub fn create_router(state: Arc<AppState>) -> Router {
@@ -22,6 +51,20 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/api/words/:id",
             get(get_one_word).put(put_word).delete(delete_word),
         )
+        .route("/api/words/duplicates", get(get_duplicate_words))
+        .route("/api/words/:id/merge", post(post_merge))
+        .route("/api/words/:id/answer", post(post_answer))
+        .route("/api/words/:id/tags", get(get_word_tags).put(put_word_tags))
+        .route("/api/words/:id/reviews", get(get_word_reviews))
+        .route("/api/words/:id/revisions", get(get_word_revisions))
+        .route(
+            "/api/words/:id/revisions/:revision_id/revert",
+            post(revert_word),
+        )
+        .route(
+            "/api/words/:id/spellings",
+            get(get_spellings).put(put_spellings),
+        )
         .route("/api/question/:id", get(generate_question))
         .route(
             "/api/collections",
@@ -33,7 +76,65 @@ pub fn create_router(state: Arc<AppState>) -> Router {
                 .put(put_collection)
                 .delete(delete_collection),
         )
-        .with_state(state);
+        .route("/api/collections/:id/clone", post(clone_collection))
+        .route("/api/collections/:id/members", get(get_members))
+        .route(
+            "/api/collections/:id/members/:user_id",
+            put(put_member).delete(delete_member),
+        )
+        .route("/api/collections/:id/invites", post(post_invite))
+        .route("/api/collections/:id/activity", get(get_activity))
+        .route("/api/invites/:token", post(accept_invite))
+        .route("/api/discover", get(discover_collections))
+        .route("/api/tags", get(get_all_tags).post(post_tag))
+        .route(
+            "/api/tags/:id",
+            get(get_one_tag).put(put_tag).delete(delete_tag),
+        )
+        .route("/api/export", get(get_export))
+        .route(
+            "/api/import",
+            post(post_import).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
+        )
+        .route("/api/trash", get(get_trash))
+        .route("/api/trash/:kind/:id/restore", post(restore_from_trash))
+        .route("/api/users", post(post_user))
+        .route("/api/users/me", get(get_me))
+        .route("/api/sessions", post(post_session))
+        .route("/api/admin/llm_stats", get(get_llm_stats))
+        .route("/api/admin/integrity", get(get_integrity))
+        .route("/api/admin/checkpoint", post(post_checkpoint))
+        .route("/api/admin/backups", post(post_backup))
+        .route(
+            "/api/admin/prompt_templates",
+            get(get_all_prompt_templates).post(post_prompt_template),
+        )
+        .route(
+            "/api/admin/prompt_templates/:id",
+            get(get_one_prompt_template),
+        )
+        .route(
+            "/api/admin/prompt_templates/:id/preview",
+            get(preview_prompt_template),
+        )
+        .route("/api/openapi.json", get(get_openapi))
+        .route("/api/docs", get(get_docs))
+        .route("/metrics", get(get_metrics))
+        .route("/healthz", get(get_healthz))
+        .route("/readyz", get(get_readyz))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            metrics::track_requests,
+        ))
+        .with_state(state)
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(telemetry::request_span)
+                .on_response(telemetry::record_response),
+        )
+        .layer(PropagateRequestIdLayer::x_request_id())
+        // Outermost, so the request span already sees the id
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
 
     app
 }
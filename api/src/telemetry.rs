@@ -0,0 +1,204 @@
+//! Logging setup, and what keeps secrets and user content out of the logs.
+//! Request spans only carry the matched route, never the path or query, since
+//! those hold invite tokens and search terms.
+
+use std::{
+    io::{self, IsTerminal},
+    time::Duration,
+};
+
+use axum::{
+    extract::MatchedPath,
+    http::{Request, Response},
+};
+use tracing::{field::Empty, Span};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+/// Used unless `RUST_LOG` is set. `drue=debug` adds a span per query.
+const DEFAULT_FILTER: &str = "drue=info,tower_http=info";
+
+const REDACTED: &str = "[redacted]";
+
+/// Fields whose values are masked, whether in JSON or as `key=value`.
+const SECRET_FIELDS: [&str; 4] = ["password", "password_hash", "api_key", "token"];
+
+const QUOTES: [char; 2] = ['"', '\''];
+
+const CLOSING: [char; 5] = [',', '.', ')', ']', '}'];
+
+/// Installs the global subscriber. `format` is `json` for one JSON object per
+/// line, anything else gives human readable output. Every span logs when it
+/// closes, with `time.busy` and `time.idle` as its latency.
+pub fn init(format: &str) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(io::stdout().is_terminal());
+
+    match format {
+        "json" => subscriber.json().with_current_span(true).init(),
+        _ => subscriber.pretty().init(),
+    }
+}
+
+/// Span of an HTTP request, with the id set by the request id layer.
+pub fn request_span<B>(request: &Request<B>) -> Span {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str)
+        .unwrap_or("unmatched");
+
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|id| id.to_str().ok())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        route,
+        request_id,
+        status = Empty,
+        latency_ms = Empty,
+    )
+}
+
+pub fn record_response<B>(response: &Response<B>, latency: Duration, span: &Span) {
+    span.record("status", response.status().as_u16());
+    span.record("latency_ms", latency.as_millis() as u64);
+}
+
+/// Masks API keys, bearer tokens and the values of secret fields, as
+/// providers quote the key they were sent in some error messages.
+pub fn redact(text: &str) -> String {
+    let mut secret_follows = false;
+    // Quoted values may go on over several words
+    let mut in_quote = false;
+
+    text.split(' ')
+        .map(|word| {
+            if in_quote {
+                in_quote = !word.trim_end_matches(CLOSING).ends_with(QUOTES);
+                return mask(word);
+            }
+
+            if std::mem::take(&mut secret_follows)
+                || word.trim_start_matches(QUOTES).starts_with("sk-")
+            {
+                in_quote = opens_quote(word);
+                return mask(word);
+            }
+
+            secret_follows = word.eq_ignore_ascii_case("bearer");
+
+            match secret_field(word) {
+                // Compact JSON or `key=value`, the value is part of the word
+                Some(value) if value < word.len() => {
+                    in_quote = opens_quote(&word[value..]);
+                    format!("{}{}", &word[..value], mask(&word[value..]))
+                }
+                Some(_) => {
+                    secret_follows = true;
+                    word.to_string()
+                }
+                None => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Replaces a value, keeping the punctuation that closes it.
+fn mask(value: &str) -> String {
+    let secret = value.trim_end_matches(CLOSING);
+
+    format!("{}{}", REDACTED, &value[secret.len()..])
+}
+
+/// Whether the value starts a quoted string that doesn't end with it.
+fn opens_quote(value: &str) -> bool {
+    let value = value.trim_end_matches(CLOSING);
+
+    value.starts_with(QUOTES) && (value.len() == 1 || !value.ends_with(QUOTES))
+}
+
+/// Where the value starts if the word names one of [`SECRET_FIELDS`], as in
+/// `"password":` or `api_key=`.
+fn secret_field(word: &str) -> Option<usize> {
+    let key_start = word.len() - word.trim_start_matches(['{', '"', '\'']).len();
+    let rest = &word[key_start..];
+    let key_len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))?;
+
+    if !SECRET_FIELDS
+        .iter()
+        .any(|field| rest[..key_len].eq_ignore_ascii_case(field))
+    {
+        return None;
+    }
+
+    let after_key = &rest[key_len..];
+    let quote_len = after_key.len() - after_key.trim_start_matches(QUOTES).len();
+
+    after_key[quote_len..]
+        .starts_with([':', '='])
+        .then_some(key_start + key_len + quote_len + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::redact;
+
+    #[test]
+    fn redacts_bearer_tokens() {
+        assert_eq!(
+            redact("Authorization: Bearer 3f9a2b7c"),
+            "Authorization: Bearer [redacted]"
+        );
+        assert_eq!(
+            redact("authorization: bearer 3f9a2b7c, retrying"),
+            "authorization: bearer [redacted], retrying"
+        );
+    }
+
+    #[test]
+    fn redacts_password_fields() {
+        assert_eq!(
+            redact(r#"{"name": "kari", "password": "correct horse"}"#),
+            r#"{"name": "kari", "password": [redacted] [redacted]}"#
+        );
+        assert_eq!(
+            redact(r#"{"password":"hunter2","name":"kari"}"#),
+            r#"{"password":[redacted]}"#
+        );
+        assert_eq!(
+            redact("password=hunter2 user=kari"),
+            "password=[redacted] user=kari"
+        );
+        assert_eq!(
+            redact(r#"{"password": "a b c", "name": "kari"}"#),
+            r#"{"password": [redacted] [redacted] [redacted], "name": "kari"}"#
+        );
+        assert_eq!(redact("Wrong password."), "Wrong password.");
+    }
+
+    #[test]
+    fn redacts_openai_keys() {
+        assert_eq!(
+            redact("Incorrect API key provided: sk-proj-Ab12Cd34. You can find your API key at https://platform.openai.com/account/api-keys."),
+            "Incorrect API key provided: [redacted]. You can find your API key at https://platform.openai.com/account/api-keys."
+        );
+        assert_eq!(
+            redact(r#"{"api_key": "sk-Ab12"}"#),
+            r#"{"api_key": [redacted]}"#
+        );
+        assert_eq!(
+            redact("The model `gpt-4o` does not exist, prompt_tokens: 12"),
+            "The model `gpt-4o` does not exist, prompt_tokens: 12"
+        );
+    }
+}
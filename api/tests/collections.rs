@@ -0,0 +1,454 @@
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use serde_json::json;
+
+use common::{TestApp, ADMIN};
+
+#[tokio::test]
+async fn creates_reads_updates_and_deletes_collections() {
+    let app = TestApp::new().await;
+    let admin = app.user(ADMIN).await;
+    let hus = app.word("hus", "house").await;
+    let bil = app.word("bil", "car").await;
+
+    let id = app.collection("Basics", None, &[hus]).await;
+    let path = format!("/api/collections/{}", id);
+
+    let response = app.get(&path, None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Basics");
+    assert_eq!(response.body["words"].as_array().unwrap().len(), 1);
+
+    let response = app
+        .put(
+            &path,
+            None,
+            json!({ "description": "First words", "words": [hus, bil] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["description"], "First words");
+    assert_eq!(response.body["words"].as_array().unwrap().len(), 2);
+
+    let response = app.get("/api/collections", None).await;
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+
+    let response = app.delete(&path, Some(&admin)).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.get(&path, None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.get("/api/collections", None).await;
+    assert_eq!(response.body, json!([]));
+}
+
+#[tokio::test]
+async fn missing_collections_are_not_found() {
+    let app = TestApp::new().await;
+
+    for response in [
+        app.get("/api/collections/42", None).await,
+        app.put("/api/collections/42", None, json!({ "name": "Basics" }))
+            .await,
+        app.delete("/api/collections/42", None).await,
+    ] {
+        assert_eq!(response.status, StatusCode::NOT_FOUND, "{}", response.body);
+        assert_eq!(response.error(), "not_found");
+    }
+}
+
+#[tokio::test]
+async fn rejects_invalid_columns() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post("/api/collections", None, json!({ "description": "" }))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let admin = app.user(ADMIN).await;
+    let id = app.collection("Basics", None, &[]).await;
+
+    let response = app
+        .put(
+            &format!("/api/collections/{}", id),
+            Some(&admin),
+            json!({ "visibility": "everyone" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.error(), "constraint_violation");
+}
+
+#[tokio::test]
+async fn rejects_stale_etags() {
+    let app = TestApp::new().await;
+    let id = app.collection("Basics", None, &[]).await;
+    let path = format!("/api/collections/{}", id);
+
+    let etag = app.get(&path, None).await.etag();
+
+    app.put(&path, None, json!({ "name": "Basics 1" })).await;
+
+    let response = app
+        .request(
+            Method::DELETE,
+            &path,
+            None,
+            &[(header::IF_MATCH, &etag)],
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED);
+    assert_eq!(app.get(&path, None).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn concurrent_writes_with_one_etag_let_one_through() {
+    let app = TestApp::new().await;
+    let hus = app.word("hus", "house").await;
+    let bil = app.word("bil", "car").await;
+    let id = app.collection("Basics", None, &[]).await;
+    let path = format!("/api/collections/{}", id);
+
+    let etag = app.get(&path, None).await.etag();
+    let if_match = [(header::IF_MATCH, etag.as_str())];
+
+    // Both pass the ETag check before either has written
+    let put = |words: &[i64]| {
+        app.request(
+            Method::PUT,
+            &path,
+            None,
+            &if_match,
+            Some(json!({ "words": words })),
+        )
+    };
+    let (first, second) = tokio::join!(put(&[hus]), put(&[bil]));
+
+    let mut statuses = [first.status, second.status];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::PRECONDITION_FAILED]);
+
+    let response = app.get(&path, None).await;
+    assert_eq!(response.body["words"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn private_collections_are_hidden_from_others() {
+    let app = TestApp::new().await;
+    let owner = app.user("kari").await;
+    let other = app.user("ola").await;
+
+    let id = app.collection("Mine", Some(&owner), &[]).await;
+    let path = format!("/api/collections/{}", id);
+
+    assert_eq!(app.get(&path, Some(&owner)).await.status, StatusCode::OK);
+    assert_eq!(
+        app.get(&path, Some(&other)).await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(app.get(&path, None).await.status, StatusCode::NOT_FOUND);
+
+    let response = app
+        .put(&path, Some(&other), json!({ "name": "Yours" }))
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.error(), "forbidden");
+
+    let response = app.delete(&path, Some(&other)).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn ownerless_collections_are_managed_by_admins() {
+    let app = TestApp::new().await;
+    let user = app.user("kari").await;
+    let admin = app.user(ADMIN).await;
+    let id = app.collection("Basics", None, &[]).await;
+    let path = format!("/api/collections/{}", id);
+
+    // Anyone can still edit them
+    for token in [None, Some(user.as_str())] {
+        let response = app.put(&path, token, json!({ "name": "Shared" })).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+        let response = app
+            .put(&path, token, json!({ "visibility": "public" }))
+            .await;
+        assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+        let response = app.delete(&path, token).await;
+        assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+        let response = app
+            .post(
+                &format!("{}/invites", path),
+                token,
+                json!({ "role": "editor" }),
+            )
+            .await;
+        assert!(response.status.is_client_error(), "{}", response.status);
+    }
+
+    let response = app
+        .put(&path, Some(&admin), json!({ "visibility": "public" }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    let response = app.delete(&path, Some(&admin)).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn system_columns_are_refused() {
+    let app = TestApp::new().await;
+    let owner = app.user("kari").await;
+    let id = app.collection("Mine", Some(&owner), &[]).await;
+
+    // Owners come from the session, never from the body
+    for token in [None, Some(owner.as_str())] {
+        let response = app
+            .post(
+                "/api/collections",
+                token,
+                json!({ "name": "Mine", "description": "", "owner_id": 1 }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.error(), "system_column");
+    }
+
+    for column in ["id", "version", "updated_at", "deleted_at", "owner_id"] {
+        let response = app
+            .put(
+                &format!("/api/collections/{}", id),
+                Some(&owner),
+                json!({ column: 2 }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{}", column);
+        assert_eq!(response.body["details"]["column"], column);
+    }
+
+    assert_eq!(app.count("collections").await, 1);
+    assert_eq!(app.get("/api/trash", Some(&owner)).await.body, json!([]));
+}
+
+#[tokio::test]
+async fn invites_members() {
+    let app = TestApp::new().await;
+    let owner = app.user("kari").await;
+    let editor = app.user("ola").await;
+
+    let id = app.collection("Shared", Some(&owner), &[]).await;
+    let path = format!("/api/collections/{}", id);
+
+    // Only owners can invite
+    let response = app
+        .post(
+            &format!("{}/invites", path),
+            Some(&editor),
+            json!({ "role": "editor" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = app
+        .post(
+            &format!("{}/invites", path),
+            Some(&owner),
+            json!({ "role": "editor" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    let invite = response.body["token"].as_str().unwrap().to_string();
+
+    let response = app
+        .post(&format!("/api/invites/{}", invite), None, json!(null))
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .post(
+            &format!("/api/invites/{}", invite),
+            Some(&editor),
+            json!(null),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    let response = app
+        .put(&path, Some(&editor), json!({ "name": "Ours" }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    // Editors can't change who sees the collection, or anything else but
+    // name, description and words
+    for body in [
+        json!({ "visibility": "public" }),
+        json!({ "filter": { "tags": ["animals"] } }),
+        json!({ "cloned_from": id }),
+        json!({ "deleted_at": "2024-01-01 00:00:00" }),
+        json!({ "name": "Ours", "source_language": "de" }),
+    ] {
+        let response = app.put(&path, Some(&editor), body.clone()).await;
+        assert_eq!(response.status, StatusCode::FORBIDDEN, "{}", body);
+    }
+    assert_eq!(app.get(&path, Some(&owner)).await.body["name"], "Ours");
+
+    let response = app.get(&format!("{}/members", path), Some(&owner)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 2);
+
+    let response = app
+        .post("/api/invites/unknown", Some(&editor), json!(null))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn invites_to_trashed_collections_are_not_found() {
+    let app = TestApp::new().await;
+    let owner = app.user("kari").await;
+    let other = app.user("ola").await;
+
+    let id = app.collection("Shared", Some(&owner), &[]).await;
+    let path = format!("/api/collections/{}", id);
+
+    let response = app
+        .post(
+            &format!("{}/invites", path),
+            Some(&owner),
+            json!({ "role": "editor" }),
+        )
+        .await;
+    let invite = response.body["token"].as_str().unwrap().to_string();
+
+    assert_eq!(app.delete(&path, Some(&owner)).await.status, StatusCode::OK);
+
+    let response = app
+        .post(
+            &format!("/api/invites/{}", invite),
+            Some(&other),
+            json!(null),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(app.count("collection_members").await, 1);
+}
+
+#[tokio::test]
+async fn discovers_and_clones_public_collections() {
+    let app = TestApp::new().await;
+    let owner = app.user("kari").await;
+    let other = app.user("ola").await;
+    let hus = app.word("hus", "house").await;
+
+    let id = app.collection("Houses", Some(&owner), &[hus]).await;
+    app.collection("Secret", Some(&owner), &[]).await;
+
+    let response = app
+        .put(
+            &format!("/api/collections/{}", id),
+            Some(&owner),
+            json!({ "visibility": "public" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    let response = app.get("/api/discover?q=house", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+    assert_eq!(response.body[0]["name"], "Houses");
+
+    let response = app
+        .post(
+            &format!("/api/collections/{}/clone", id),
+            Some(&other),
+            json!({ "words": true }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["cloned_from"], id);
+    assert_eq!(response.body["visibility"], "private");
+    assert_eq!(response.body["words"][0]["word"], "hus");
+    assert_ne!(response.body["words"][0]["id"], hus);
+
+    let response = app
+        .post(&format!("/api/collections/{}/clone", id), None, json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn discover_matches_wildcards_literally() {
+    let app = TestApp::new().await;
+    let owner = app.user("kari").await;
+
+    for name in ["100% Norsk", "1000 ord", "snake_case", "back\\slash"] {
+        let id = app.collection(name, Some(&owner), &[]).await;
+        let response = app
+            .put(
+                &format!("/api/collections/{}", id),
+                Some(&owner),
+                json!({ "visibility": "public" }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    }
+
+    for (q, expected) in [
+        ("100%25", vec!["100% Norsk"]),
+        ("%25", vec!["100% Norsk"]),
+        ("_", vec!["snake_case"]),
+        ("0_", vec![]),
+        ("%5C", vec!["back\\slash"]),
+    ] {
+        let response = app.get(&format!("/api/discover?q={}", q), None).await;
+        assert_eq!(response.status, StatusCode::OK);
+
+        let names: Vec<&str> = response
+            .body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, expected, "{}", q);
+    }
+}
+
+#[tokio::test]
+async fn restores_from_the_trash() {
+    let app = TestApp::new().await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+    let admin = app.user(ADMIN).await;
+
+    app.delete(&format!("/api/words/{}", hus), None).await;
+    app.delete(&format!("/api/collections/{}", id), Some(&admin))
+        .await;
+
+    let response = app.get("/api/trash", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 2);
+
+    for path in [
+        format!("/api/trash/words/{}/restore", hus),
+        format!("/api/trash/collections/{}/restore", id),
+    ] {
+        let response = app.post(&path, None, json!(null)).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    }
+
+    let response = app.get(&format!("/api/collections/{}", id), None).await;
+    assert_eq!(response.body["words"][0]["word"], "hus");
+
+    let response = app
+        .post("/api/trash/words/42/restore", None, json!(null))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
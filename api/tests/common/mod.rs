@@ -0,0 +1,342 @@
+//! Runs the router against a fresh in-memory database, with OpenAI replaced by
+//! a local server answering from a script.
+
+#![allow(dead_code)]
+
+use std::{
+    collections::VecDeque,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::State,
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use drue::{
+    controllers::users::{Credentials, UsersController},
+    metrics::Metrics,
+    routes::create_router,
+    AppState, MIGRATOR,
+};
+use serde_json::{json, Value};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+
+pub const ADMIN: &str = "admin";
+pub const PASSWORD: &str = "correct horse";
+
+pub struct TestApp {
+    pub state: Arc<AppState>,
+    router: Router,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+impl TestResponse {
+    pub fn etag(&self) -> String {
+        self.headers
+            .get(header::ETAG)
+            .expect("response has no ETag")
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// The `error` code of an error body.
+    pub fn error(&self) -> &str {
+        self.body["error"].as_str().unwrap_or_default()
+    }
+}
+
+impl TestApp {
+    /// An app whose LLM calls fail, for tests that don't generate questions.
+    pub async fn new() -> Self {
+        Self::with_llm("http://127.0.0.1:1/v1/chat/completions", 0).await
+    }
+
+    /// An app sending LLM calls to `llm_url`, keeping `question_pool_size`
+    /// questions per word.
+    pub async fn with_llm(llm_url: &str, question_pool_size: i64) -> Self {
+        Self::build(llm_url, question_pool_size, None).await
+    }
+
+    /// An app whose daily token budget is spent, so questions are built from
+    /// the collection without calling the LLM.
+    pub async fn offline() -> Self {
+        Self::build("http://127.0.0.1:1/v1/chat/completions", 0, Some(0)).await
+    }
+
+    async fn build(
+        llm_url: &str,
+        question_pool_size: i64,
+        daily_token_budget: Option<i64>,
+    ) -> Self {
+        let pool = memory_pool().await;
+
+        MIGRATOR.run(&pool).await.expect("migrations failed");
+
+        let state = Arc::new(AppState {
+            pool,
+            http_client: reqwest::Client::new(),
+            llm_url: llm_url.to_string(),
+            question_pool_size,
+            question_retries: 1,
+            learner_level: "beginner".to_string(),
+            daily_token_budget,
+            user_daily_token_budget: None,
+            prompt_token_price: 2.5,
+            completion_token_price: 10.0,
+            trash_retention_days: 30,
+            admin_users: vec![ADMIN.to_string()],
+            backup: None,
+            metrics: Metrics::new(),
+            pregenerating: Default::default(),
+        });
+
+        TestApp {
+            router: create_router(state.clone()),
+            state,
+        }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.state.pool
+    }
+
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        token: Option<&str>,
+        headers: &[(header::HeaderName, &str)],
+        body: Option<Value>,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(path);
+
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::from(String::from_utf8_lossy(&bytes).into_owned()))
+        };
+
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    pub async fn get(&self, path: &str, token: Option<&str>) -> TestResponse {
+        self.request(Method::GET, path, token, &[], None).await
+    }
+
+    pub async fn post(&self, path: &str, token: Option<&str>, body: Value) -> TestResponse {
+        self.request(Method::POST, path, token, &[], Some(body))
+            .await
+    }
+
+    pub async fn put(&self, path: &str, token: Option<&str>, body: Value) -> TestResponse {
+        self.request(Method::PUT, path, token, &[], Some(body))
+            .await
+    }
+
+    pub async fn delete(&self, path: &str, token: Option<&str>) -> TestResponse {
+        self.request(Method::DELETE, path, token, &[], None).await
+    }
+
+    /// Registers a user and returns a session token for it.
+    pub async fn user(&self, name: &str) -> String {
+        let credentials = json!({ "name": name, "password": PASSWORD });
+
+        // Administrator names are reserved over HTTP, so these are added the
+        // way `drue users add` does it
+        if self.state.admin_users.iter().any(|admin| admin == name) {
+            UsersController::new(self.pool())
+                .register(&Credentials {
+                    name: name.to_string(),
+                    password: PASSWORD.to_string(),
+                })
+                .await
+                .unwrap();
+        } else {
+            let response = self.post("/api/users", None, credentials.clone()).await;
+            assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+        }
+
+        let response = self.post("/api/sessions", None, credentials).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+        response.body["token"].as_str().unwrap().to_string()
+    }
+
+    /// Creates a word with empty media and returns its id.
+    pub async fn word(&self, word: &str, translation: &str) -> i64 {
+        let response = self
+            .post(
+                "/api/words",
+                None,
+                json!({ "word": word, "translation": translation, "image": "", "audio": "" }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+        response.body["id"].as_i64().unwrap()
+    }
+
+    /// Creates a collection of the given words and returns its id.
+    pub async fn collection(&self, name: &str, token: Option<&str>, words: &[i64]) -> i64 {
+        let response = self
+            .post(
+                "/api/collections",
+                token,
+                json!({ "name": name, "description": "", "words": words }),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+        response.body["id"].as_i64().unwrap()
+    }
+
+    pub async fn count(&self, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(self.pool())
+            .await
+            .unwrap()
+    }
+}
+
+/// A single connection that is never closed, since every connection to
+/// `:memory:` opens a database of its own.
+async fn memory_pool() -> SqlitePool {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:")
+        .unwrap()
+        .foreign_keys(true);
+
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .unwrap()
+}
+
+/// Stands in for the chat completions endpoint, answering requests with the
+/// queued replies in order and repeating the last one when they run out.
+pub struct MockLlm {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+#[derive(Clone)]
+struct Script {
+    replies: Arc<Mutex<VecDeque<Value>>>,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl MockLlm {
+    pub async fn start(replies: Vec<Value>) -> Self {
+        let script = Script {
+            replies: Arc::new(Mutex::new(replies.into())),
+            requests: Arc::new(Mutex::new(vec![])),
+        };
+
+        let requests = script.requests.clone();
+
+        let app = Router::new()
+            .route("/v1/chat/completions", post(complete))
+            .with_state(script);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        MockLlm {
+            url: format!("http://{}/v1/chat/completions", address),
+            requests,
+        }
+    }
+
+    /// Bodies of the requests received so far.
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn complete(State(script): State<Script>, body: String) -> Json<Value> {
+    script
+        .requests
+        .lock()
+        .unwrap()
+        .push(serde_json::from_str(&body).unwrap_or(Value::Null));
+
+    let mut replies = script.replies.lock().unwrap();
+
+    let reply = if replies.len() > 1 {
+        replies.pop_front().unwrap()
+    } else {
+        replies.front().cloned().unwrap_or(Value::Null)
+    };
+
+    Json(reply)
+}
+
+/// A chat completion whose message is `content`.
+pub fn completion(content: &str) -> Value {
+    json!({
+        "choices": [{ "message": { "role": "assistant", "content": content } }],
+        "usage": { "prompt_tokens": 120, "completion_tokens": 30 }
+    })
+}
+
+/// A completion holding a question that passes validation.
+pub fn valid_question() -> Value {
+    completion(
+        &json!({
+            "question": "Which of these can you live in?",
+            "options": [
+                { "name": "hus", "correct": true },
+                { "name": "bil", "correct": false },
+                { "name": "eple", "correct": false }
+            ]
+        })
+        .to_string(),
+    )
+}
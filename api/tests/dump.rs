@@ -0,0 +1,65 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{TestApp, ADMIN};
+
+#[tokio::test]
+async fn exports_everything_but_the_trash() {
+    let app = TestApp::new().await;
+    let admin = app.user(ADMIN).await;
+    let hus = app.word("hus", "house").await;
+    let bil = app.word("bil", "car").await;
+    let id = app.collection("Basics", None, &[hus, bil]).await;
+
+    let response = app
+        .put(&format!("/api/words/{}/tags", hus), None, json!(["noun"]))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    app.delete(&format!("/api/words/{}", bil), None).await;
+    app.post("/api/tags", None, json!({ "name": "unused" }))
+        .await;
+
+    let response = app.get("/api/export", Some(&admin)).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    let dump = response.body;
+
+    assert_eq!(dump["users"].as_array().unwrap().len(), 1);
+    assert_eq!(dump["tags"], json!(["noun", "unused"]));
+    assert_eq!(dump["words"].as_array().unwrap().len(), 1);
+    assert_eq!(dump["words"][0]["id"], hus);
+    assert_eq!(dump["words"][0]["tags"], json!(["noun"]));
+    assert_eq!(dump["collections"][0]["id"], id);
+    assert_eq!(dump["collections"][0]["word_ids"], json!([hus]));
+}
+
+#[tokio::test]
+async fn restores_what_it_exported() {
+    let app = TestApp::new().await;
+    let admin = app.user(ADMIN).await;
+    let hus = app.word("hus", "house").await;
+    app.collection("Basics", None, &[hus]).await;
+    app.put(&format!("/api/words/{}/tags", hus), None, json!(["noun"]))
+        .await;
+    app.post("/api/tags", None, json!({ "name": "unused" }))
+        .await;
+
+    let dump = app.get("/api/export", Some(&admin)).await.body;
+
+    let copy = TestApp::new().await;
+    let admin = copy.user(ADMIN).await;
+    let response = copy
+        .post("/api/import?mode=restore", Some(&admin), dump.clone())
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    // Versions and users are the copy's own
+    let restored = copy.get("/api/export", Some(&admin)).await.body;
+    for key in ["tags", "collections", "reviews"] {
+        assert_eq!(restored[key], dump[key], "{}", key);
+    }
+    for key in ["id", "word", "tags", "spellings"] {
+        assert_eq!(restored["words"][0][key], dump["words"][0][key], "{}", key);
+    }
+}
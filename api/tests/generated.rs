@@ -0,0 +1,31 @@
+//! The OpenAPI document and the frontend types are checked in, so changes to
+//! the API show up in review. These fail when either is out of date.
+
+use std::fs;
+
+use drue::{codegen, endpoints::docs::ApiDoc};
+use utoipa::OpenApi;
+
+fn checked_in(path: &str) -> String {
+    fs::read_to_string(format!("{}/{}", env!("CARGO_MANIFEST_DIR"), path)).unwrap()
+}
+
+#[test]
+fn openapi_document_is_up_to_date() {
+    let generated = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+
+    assert!(
+        checked_in("openapi.json") == generated,
+        "api/openapi.json is out of date, run `cargo run -- openapi > openapi.json` in api/"
+    );
+}
+
+#[test]
+fn frontend_types_are_up_to_date() {
+    let generated = codegen::typescript().unwrap();
+
+    assert!(
+        checked_in("../app/src/types.ts") == generated,
+        "app/src/types.ts is out of date, run `cargo run --manifest-path ../api/Cargo.toml -- codegen src/types.ts` in app/"
+    );
+}
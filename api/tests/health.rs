@@ -0,0 +1,82 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::Value;
+
+use common::{valid_question, MockLlm, TestApp};
+
+fn check<'a>(body: &'a Value, name: &str) -> &'a Value {
+    body["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["name"] == name)
+        .unwrap_or_else(|| panic!("no {} check in {}", name, body))
+}
+
+#[tokio::test]
+async fn is_alive() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/healthz", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["ok"], true);
+}
+
+#[tokio::test]
+async fn is_ready_once_migrated() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/readyz", None).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["ready"], true);
+    assert_eq!(check(&response.body, "database")["ok"], true);
+    assert_eq!(check(&response.body, "migrations")["ok"], true);
+
+    // The provider is only checked when asked for
+    assert_eq!(response.body["checks"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn is_not_ready_with_pending_migrations() {
+    let app = TestApp::new().await;
+
+    let version: i64 = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+        .fetch_one(app.pool())
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM _sqlx_migrations WHERE version = ?")
+        .bind(version)
+        .execute(app.pool())
+        .await
+        .unwrap();
+
+    let response = app.get("/readyz", None).await;
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(response.body["ready"], false);
+    assert_eq!(check(&response.body, "database")["ok"], true);
+
+    let migrations = check(&response.body, "migrations");
+    assert_eq!(migrations["ok"], false);
+    assert!(migrations["detail"]
+        .as_str()
+        .unwrap()
+        .contains(&version.to_string()));
+}
+
+#[tokio::test]
+async fn checks_the_llm_provider_when_asked() {
+    let llm = MockLlm::start(vec![valid_question()]).await;
+    let app = TestApp::with_llm(&llm.url, 0).await;
+
+    let response = app.get("/readyz?llm=true", None).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(check(&response.body, "llm")["ok"], true);
+
+    let app = TestApp::new().await;
+
+    let response = app.get("/readyz?llm=true", None).await;
+    assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(check(&response.body, "llm")["ok"], false);
+    assert_eq!(check(&response.body, "database")["ok"], true);
+}
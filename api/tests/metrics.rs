@@ -0,0 +1,101 @@
+mod common;
+
+use axum::http::{header, StatusCode};
+use serde_json::json;
+
+use common::{valid_question, MockLlm, TestApp};
+
+/// The value of the sample with exactly these name and labels.
+fn sample(metrics: &str, series: &str) -> Option<f64> {
+    metrics
+        .lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+        .map(|value| value.parse().unwrap())
+}
+
+#[tokio::test]
+async fn counts_requests_llm_calls_and_questions() {
+    let llm = MockLlm::start(vec![valid_question()]).await;
+    let app = TestApp::with_llm(&llm.url, 0).await;
+    let token = app.user("kari").await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+
+    let path = format!("/api/question/{}", id);
+    assert_eq!(app.get(&path, None).await.status, StatusCode::OK);
+    app.get("/api/words/42", None).await;
+    app.post(
+        &format!("/api/words/{}/answer", hus),
+        Some(&token),
+        json!({ "answer": "hus" }),
+    )
+    .await;
+
+    let response = app.get("/metrics", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.headers[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+    let metrics = response.body.as_str().unwrap();
+
+    for (series, value) in [
+        (
+            r#"drue_http_requests_total{method="GET",route="/api/question/:id",status="200"}"#,
+            1.0,
+        ),
+        // Routes are labelled by pattern, not by path
+        (
+            r#"drue_http_requests_total{method="GET",route="/api/words/:id",status="404"}"#,
+            1.0,
+        ),
+        (
+            r#"drue_llm_calls_total{model="gpt-4o-2024-08-06",outcome="success"}"#,
+            1.0,
+        ),
+        (
+            r#"drue_llm_tokens_total{kind="prompt",model="gpt-4o-2024-08-06"}"#,
+            120.0,
+        ),
+        (r#"drue_questions_served_total{source="generated"}"#, 1.0),
+        ("drue_active_learners", 1.0),
+        ("drue_db_pool_max_connections", 1.0),
+    ] {
+        assert_eq!(sample(metrics, series), Some(value), "{}", series);
+    }
+
+    assert!(!metrics.contains("/api/words/42"));
+}
+
+#[tokio::test]
+async fn counts_pool_hits_and_llm_failures() {
+    let app = TestApp::new().await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+    let path = format!("/api/question/{}", id);
+
+    assert_eq!(app.get(&path, None).await.status, StatusCode::BAD_GATEWAY);
+
+    // As if pre-generated after the failed call
+    sqlx::query("INSERT INTO generated_questions (word_id, kind, question) SELECT id, 'multiple_choice', ? FROM words")
+        .bind(valid_question()["choices"][0]["message"]["content"].as_str())
+        .execute(app.pool())
+        .await
+        .unwrap();
+    assert_eq!(app.get(&path, None).await.status, StatusCode::OK);
+
+    let metrics = app.get("/metrics", None).await.body;
+    let metrics = metrics.as_str().unwrap();
+
+    assert_eq!(
+        sample(
+            metrics,
+            r#"drue_llm_calls_total{model="gpt-4o-2024-08-06",outcome="request_error"}"#
+        ),
+        Some(1.0)
+    );
+    assert_eq!(
+        sample(metrics, r#"drue_questions_served_total{source="pool"}"#),
+        Some(1.0)
+    );
+}
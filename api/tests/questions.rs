@@ -0,0 +1,311 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use drue::{
+    controllers::{collections::CollectionsController, controller::Controller},
+    jobs::pregenerate,
+};
+
+use common::{completion, valid_question, MockLlm, TestApp};
+
+#[tokio::test]
+async fn generates_questions_on_demand() {
+    let llm = MockLlm::start(vec![valid_question()]).await;
+    let app = TestApp::with_llm(&llm.url, 0).await;
+    let token = app.user("kari").await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+
+    let response = app
+        .get(&format!("/api/question/{}", id), Some(&token))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["question"], "Which of these can you live in?");
+    assert_eq!(response.body["options"].as_array().unwrap().len(), 3);
+
+    let requests = llm.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0]["messages"].to_string().contains("hus"));
+
+    assert_eq!(app.count("generated_questions").await, 1);
+
+    let (tokens, user_id): (i64, Option<i64>) =
+        sqlx::query_as("SELECT prompt_tokens + completion_tokens, user_id FROM llm_calls")
+            .fetch_one(app.pool())
+            .await
+            .unwrap();
+    assert_eq!(tokens, 150);
+    assert!(user_id.is_some());
+}
+
+#[tokio::test]
+async fn serves_pregenerated_questions() {
+    let llm = MockLlm::start(vec![valid_question()]).await;
+    let app = TestApp::with_llm(&llm.url, 2).await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+
+    // Creating the collection fills the pool in the background
+    for _ in 0..100 {
+        if app.count("generated_questions").await == 2 {
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(app.count("generated_questions").await, 2);
+    assert_eq!(llm.requests().len(), 2);
+
+    let response = app.get(&format!("/api/question/{}", id), None).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(llm.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn fills_each_pool_once_at_a_time() {
+    let llm = MockLlm::start(vec![valid_question()]).await;
+    let app = TestApp::with_llm(&llm.url, 2).await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+    let collection = CollectionsController::new(app.pool())
+        .get_one(id)
+        .await
+        .unwrap();
+
+    // On top of the run started by creating the collection
+    for _ in 0..5 {
+        pregenerate::spawn(app.state.clone(), collection.clone(), vec![hus]);
+    }
+
+    for _ in 0..100 {
+        if app.count("generated_questions").await == 2 {
+            break;
+        }
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert_eq!(app.count("generated_questions").await, 2);
+    assert_eq!(llm.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn keeps_pools_per_source_language() {
+    let llm = MockLlm::start(vec![valid_question()]).await;
+    let app = TestApp::with_llm(&llm.url, 0).await;
+    let hus = app.word("hus", "house").await;
+    let english = app.collection("Basics", None, &[hus]).await;
+    let response = app
+        .post(
+            "/api/collections",
+            None,
+            json!({ "name": "Grundwortschatz", "description": "", "source_language": "de", "words": [hus] }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    let german = response.body["id"].as_i64().unwrap();
+
+    for id in [english, german] {
+        let response = app.get(&format!("/api/question/{}", id), None).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    }
+
+    // The question asked in English isn't served to German speakers
+    let requests = llm.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1]["messages"].to_string().contains("German"));
+
+    let languages: Vec<String> =
+        sqlx::query_scalar("SELECT source_language FROM generated_questions ORDER BY id")
+            .fetch_all(app.pool())
+            .await
+            .unwrap();
+    assert_eq!(languages, ["en", "de"]);
+
+    // Each collection is served from its own pool from now on
+    for id in [english, german] {
+        let response = app.get(&format!("/api/question/{}", id), None).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    }
+    assert_eq!(llm.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn retries_invalid_questions() {
+    let invalid = completion(
+        &json!({
+            "question": "What is this?",
+            "options": [
+                { "name": "a house", "correct": true },
+                { "name": "bil", "correct": false }
+            ]
+        })
+        .to_string(),
+    );
+
+    let llm = MockLlm::start(vec![invalid, valid_question()]).await;
+    let app = TestApp::with_llm(&llm.url, 0).await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+
+    let response = app.get(&format!("/api/question/{}", id), None).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    // The second request tells the model what was wrong with the first answer
+    let requests = llm.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1]["messages"].to_string().contains("a house"));
+
+    let outcomes: Vec<String> = sqlx::query_scalar("SELECT outcome FROM llm_calls ORDER BY id")
+        .fetch_all(app.pool())
+        .await
+        .unwrap();
+    assert_eq!(outcomes, ["invalid_question", "success"]);
+}
+
+#[tokio::test]
+async fn reports_questions_that_stay_invalid() {
+    let llm = MockLlm::start(vec![completion("not json")]).await;
+    let app = TestApp::with_llm(&llm.url, 0).await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+
+    let response = app.get(&format!("/api/question/{}", id), None).await;
+    assert_eq!(response.status, StatusCode::BAD_GATEWAY);
+    assert_eq!(response.error(), "llm_malformed_response");
+    assert_eq!(llm.requests().len(), 2);
+    assert_eq!(app.count("generated_questions").await, 0);
+}
+
+#[tokio::test]
+async fn reports_provider_errors() {
+    let llm = MockLlm::start(vec![json!({
+        "error": {
+            "message": "Incorrect API key provided: sk-proj-abc123. You can find your key in the dashboard.",
+            "type": "invalid_request_error"
+        }
+    })])
+    .await;
+    let app = TestApp::with_llm(&llm.url, 0).await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+
+    let response = app.get(&format!("/api/question/{}", id), None).await;
+    assert_eq!(response.status, StatusCode::BAD_GATEWAY);
+    assert_eq!(response.error(), "llm_provider_error");
+
+    // The key quoted by the provider is masked
+    let message = response.body["message"].as_str().unwrap();
+    assert!(message.contains("Incorrect API key provided"));
+    assert!(!message.contains("abc123"), "{}", message);
+}
+
+#[tokio::test]
+async fn reports_unreachable_providers() {
+    let app = TestApp::new().await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Basics", None, &[hus]).await;
+
+    let response = app.get(&format!("/api/question/{}", id), None).await;
+    assert_eq!(response.status, StatusCode::BAD_GATEWAY);
+    assert_eq!(response.error(), "llm_unreachable");
+
+    let outcome: String = sqlx::query_scalar("SELECT outcome FROM llm_calls")
+        .fetch_one(app.pool())
+        .await
+        .unwrap();
+    assert_eq!(outcome, "request_error");
+}
+
+#[tokio::test]
+async fn empty_and_missing_collections_are_not_found() {
+    let app = TestApp::new().await;
+    let id = app.collection("Empty", None, &[]).await;
+
+    for path in [format!("/api/question/{}", id), "/api/question/42".into()] {
+        let response = app.get(&path, None).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND, "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn private_collections_of_others_are_not_found() {
+    let llm = MockLlm::start(vec![valid_question()]).await;
+    let app = TestApp::with_llm(&llm.url, 0).await;
+    let owner = app.user("kari").await;
+    let other = app.user("ola").await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Mine", Some(&owner), &[hus]).await;
+    let path = format!("/api/question/{}", id);
+
+    for token in [None, Some(other.as_str())] {
+        let response = app.get(&path, token).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+    }
+    assert!(llm.requests().is_empty());
+
+    let response = app.get(&path, Some(&owner)).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+}
+
+#[tokio::test]
+async fn builds_questions_offline_once_the_budget_is_spent() {
+    let app = TestApp::offline().await;
+    let mut words = vec![];
+    for (word, translation) in [
+        ("hus", "house"),
+        ("bil", "car"),
+        ("katt", "cat"),
+        ("hund", "dog"),
+    ] {
+        words.push(app.word(word, translation).await);
+    }
+
+    let response = app
+        .post(
+            "/api/collections",
+            None,
+            json!({ "name": "Basics", "description": "", "words": words, "source_language": "de" }),
+        )
+        .await;
+    let path = format!("/api/question/{}", response.body["id"]);
+
+    let response = app.get(&path, None).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert!(response.body["question"]
+        .as_str()
+        .unwrap()
+        .starts_with("Welches Wort bedeutet"));
+
+    let options = response.body["options"].as_array().unwrap();
+    assert_eq!(options.len(), 4);
+    assert_eq!(
+        options
+            .iter()
+            .filter(|option| option["correct"] == true)
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn offline_questions_need_enough_distinct_words() {
+    let app = TestApp::offline().await;
+    let hus = app.word("hus", "house").await;
+    let also_hus = app.word("Hus", "home").await;
+    let bil = app.word("bil", "car").await;
+
+    for words in [vec![hus, bil], vec![hus, also_hus, bil]] {
+        let id = app.collection("Small", None, &words).await;
+
+        let response = app.get(&format!("/api/question/{}", id), None).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+        assert_eq!(response.error(), "not_enough_words");
+        assert_eq!(response.body["details"][0]["rule"], "option_count");
+    }
+}
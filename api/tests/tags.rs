@@ -0,0 +1,81 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn creates_reads_updates_and_deletes_tags() {
+    let app = TestApp::new().await;
+
+    let response = app.post("/api/tags", None, json!({ "name": "noun" })).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    let id = response.body["id"].as_i64().unwrap();
+    let path = format!("/api/tags/{}", id);
+
+    let response = app.get(&path, None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "noun");
+
+    let response = app.put(&path, None, json!({ "name": "nouns" })).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["name"], "nouns");
+
+    let response = app.get("/api/tags", None).await;
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+
+    let response = app.delete(&path, None).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    assert_eq!(app.get(&path, None).await.status, StatusCode::NOT_FOUND);
+    assert_eq!(app.get("/api/tags", None).await.body, json!([]));
+}
+
+#[tokio::test]
+async fn creating_a_trashed_tag_restores_it() {
+    let app = TestApp::new().await;
+
+    let response = app.post("/api/tags", None, json!({ "name": "noun" })).await;
+    let id = response.body["id"].as_i64().unwrap();
+    app.delete(&format!("/api/tags/{}", id), None).await;
+
+    let response = app.post("/api/tags", None, json!({ "name": "noun" })).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["id"], id);
+    assert_eq!(app.get("/api/trash", None).await.body, json!([]));
+
+    // A tag that is not in the trash keeps its name to itself
+    let response = app.post("/api/tags", None, json!({ "name": "noun" })).await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn missing_tags_are_not_found() {
+    let app = TestApp::new().await;
+
+    for response in [
+        app.get("/api/tags/42", None).await,
+        app.put("/api/tags/42", None, json!({ "name": "noun" }))
+            .await,
+        app.delete("/api/tags/42", None).await,
+    ] {
+        assert_eq!(response.status, StatusCode::NOT_FOUND, "{}", response.body);
+        assert_eq!(response.error(), "not_found");
+    }
+}
+
+#[tokio::test]
+async fn tag_names_are_unique() {
+    let app = TestApp::new().await;
+
+    app.post("/api/tags", None, json!({ "name": "noun" })).await;
+
+    let response = app.post("/api/tags", None, json!({ "name": "noun" })).await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.error(), "conflict");
+
+    let response = app.post("/api/tags", None, json!({ "name": null })).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(app.count("tags").await, 1);
+}
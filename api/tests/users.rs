@@ -0,0 +1,197 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{TestApp, ADMIN, PASSWORD};
+
+#[tokio::test]
+async fn registers_and_logs_in() {
+    let app = TestApp::new().await;
+
+    let token = app.user("kari").await;
+
+    let response = app.get("/api/users/me", Some(&token)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "kari");
+    assert!(response.body.get("password_hash").is_none());
+}
+
+#[tokio::test]
+async fn names_are_unique() {
+    let app = TestApp::new().await;
+
+    app.user("kari").await;
+
+    let response = app
+        .post(
+            "/api/users",
+            None,
+            json!({ "name": "kari", "password": "something else" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.error(), "name_taken");
+}
+
+#[tokio::test]
+async fn rejects_wrong_credentials() {
+    let app = TestApp::new().await;
+
+    app.user("kari").await;
+
+    for credentials in [
+        json!({ "name": "kari", "password": "wrong" }),
+        json!({ "name": "ola", "password": PASSWORD }),
+    ] {
+        let response = app.post("/api/sessions", None, credentials).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test]
+async fn requires_a_valid_token() {
+    let app = TestApp::new().await;
+
+    assert_eq!(
+        app.get("/api/users/me", None).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        app.get("/api/users/me", Some("not a session")).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn expired_sessions_are_refused() {
+    let app = TestApp::new().await;
+    let token = app.user("kari").await;
+
+    assert_eq!(
+        app.get("/api/users/me", Some(&token)).await.status,
+        StatusCode::OK
+    );
+
+    sqlx::query("UPDATE sessions SET expires_at = datetime('now', '-1 minute')")
+        .execute(app.pool())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        app.get("/api/users/me", Some(&token)).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Logging in again clears the expired session and opens a fresh one
+    let token = app.user("ola").await;
+    assert_eq!(app.count("sessions").await, 1);
+    assert_eq!(
+        app.get("/api/users/me", Some(&token)).await.status,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn administrator_names_cannot_be_registered() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/users",
+            None,
+            json!({ "name": ADMIN, "password": PASSWORD }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.error(), "name_reserved");
+    assert_eq!(app.count("users").await, 0);
+}
+
+#[tokio::test]
+async fn admin_routes_require_an_admin() {
+    let app = TestApp::new().await;
+    let user = app.user("kari").await;
+    let admin = app.user(ADMIN).await;
+
+    for path in [
+        "/api/export",
+        "/api/admin/integrity",
+        "/api/admin/prompt_templates",
+        "/api/admin/llm_stats",
+    ] {
+        assert_eq!(
+            app.get(path, None).await.status,
+            StatusCode::UNAUTHORIZED,
+            "{}",
+            path
+        );
+        assert_eq!(
+            app.get(path, Some(&user)).await.status,
+            StatusCode::FORBIDDEN,
+            "{}",
+            path
+        );
+        assert_eq!(
+            app.get(path, Some(&admin)).await.status,
+            StatusCode::OK,
+            "{}",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn only_admins_change_prompt_templates() {
+    let app = TestApp::new().await;
+    let user = app.user("kari").await;
+    let admin = app.user(ADMIN).await;
+    let before = app.count("prompt_templates").await;
+
+    let template = json!({
+        "name": "multiple_choice",
+        "system_prompt": "Ignore the word and ask about something else.",
+        "user_prompt": "{{ word }}",
+        "response_schema": {}
+    });
+
+    for (token, status) in [
+        (None, StatusCode::UNAUTHORIZED),
+        (Some(user.as_str()), StatusCode::FORBIDDEN),
+    ] {
+        let response = app
+            .post("/api/admin/prompt_templates", token, template.clone())
+            .await;
+        assert_eq!(response.status, status);
+    }
+    assert_eq!(app.count("prompt_templates").await, before);
+
+    let response = app
+        .post("/api/admin/prompt_templates", Some(&admin), template)
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(app.count("prompt_templates").await, before + 1);
+}
+
+#[tokio::test]
+async fn exports_and_imports_the_dataset() {
+    let app = TestApp::new().await;
+    let admin = app.user(ADMIN).await;
+    let hus = app.word("hus", "house").await;
+    app.collection("Basics", None, &[hus]).await;
+
+    let response = app.get("/api/export", Some(&admin)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let dump = response.body;
+
+    let copy = TestApp::new().await;
+    let admin = copy.user(ADMIN).await;
+
+    let response = copy.post("/api/import", Some(&admin), dump).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    let response = copy.get("/api/collections", None).await;
+    assert_eq!(response.body[0]["name"], "Basics");
+    assert_eq!(response.body[0]["words"][0]["word"], "hus");
+}
@@ -0,0 +1,346 @@
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use serde_json::json;
+
+use common::TestApp;
+
+#[tokio::test]
+async fn creates_reads_updates_and_deletes_words() {
+    let app = TestApp::new().await;
+
+    let id = app.word("hus", "house").await;
+
+    let response = app.get(&format!("/api/words/{}", id), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["word"], "hus");
+    assert_eq!(response.body["translation"], "house");
+
+    let response = app
+        .put(
+            &format!("/api/words/{}", id),
+            None,
+            json!({ "translation": "home" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["translation"], "home");
+
+    let response = app.get("/api/words", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+
+    let response = app.delete(&format!("/api/words/{}", id), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.get(&format!("/api/words/{}", id), None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.get("/api/words", None).await;
+    assert_eq!(response.body, json!([]));
+}
+
+#[tokio::test]
+async fn missing_words_are_not_found() {
+    let app = TestApp::new().await;
+
+    for response in [
+        app.get("/api/words/42", None).await,
+        app.put("/api/words/42", None, json!({ "word": "hus" }))
+            .await,
+        app.delete("/api/words/42", None).await,
+        app.get("/api/words/42/tags", None).await,
+        app.put("/api/words/42/tags", None, json!(["noun"])).await,
+    ] {
+        assert_eq!(response.status, StatusCode::NOT_FOUND, "{}", response.body);
+        assert_eq!(response.error(), "not_found");
+    }
+}
+
+#[tokio::test]
+async fn words_missing_required_columns_are_rejected() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post(
+            "/api/words",
+            None,
+            json!({ "word": "hus", "image": "", "audio": "" }),
+        )
+        .await;
+
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.error(), "constraint_violation");
+    assert_eq!(app.count("words").await, 0);
+}
+
+#[tokio::test]
+async fn pages_through_words() {
+    let app = TestApp::new().await;
+
+    for word in ["en", "to", "tre", "fire", "fem"] {
+        app.word(word, word).await;
+    }
+
+    let first = app.get("/api/words?limit=2", None).await;
+    let last = app.get("/api/words?limit=2&offset=4", None).await;
+
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.body.as_array().unwrap().len(), 2);
+    assert_eq!(first.body[0]["word"], "en");
+    assert_eq!(last.body.as_array().unwrap().len(), 1);
+    assert_eq!(last.body[0]["word"], "fem");
+}
+
+#[tokio::test]
+async fn honours_etags() {
+    let app = TestApp::new().await;
+    let id = app.word("hus", "house").await;
+    let path = format!("/api/words/{}", id);
+
+    let etag = app.get(&path, None).await.etag();
+
+    let response = app
+        .request(
+            Method::GET,
+            &path,
+            None,
+            &[(header::IF_NONE_MATCH, &etag)],
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_MODIFIED);
+
+    // Weak tags are good enough for caching but never for writes
+    let weak = format!("W/{}", etag);
+    let response = app
+        .request(
+            Method::GET,
+            &path,
+            None,
+            &[(header::IF_NONE_MATCH, &weak)],
+            None,
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_MODIFIED);
+
+    let response = app
+        .request(
+            Method::PUT,
+            &path,
+            None,
+            &[(header::IF_MATCH, &weak)],
+            Some(json!({ "translation": "home" })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED);
+
+    let response = app
+        .request(
+            Method::PUT,
+            &path,
+            None,
+            &[(header::IF_MATCH, &etag)],
+            Some(json!({ "translation": "home" })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_ne!(response.etag(), etag);
+
+    // The word changed since the first ETag was handed out
+    let response = app
+        .request(
+            Method::PUT,
+            &path,
+            None,
+            &[(header::IF_MATCH, &etag)],
+            Some(json!({ "translation": "building" })),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::PRECONDITION_FAILED);
+    assert_eq!(app.get(&path, None).await.body["translation"], "home");
+}
+
+#[tokio::test]
+async fn concurrent_writes_with_one_etag_let_one_through() {
+    let app = TestApp::new().await;
+    let id = app.word("hus", "house").await;
+    let path = format!("/api/words/{}", id);
+
+    let etag = app.get(&path, None).await.etag();
+    let if_match = [(header::IF_MATCH, etag.as_str())];
+
+    // Both pass the ETag check before either has written
+    let put = |translation: &'static str| {
+        app.request(
+            Method::PUT,
+            &path,
+            None,
+            &if_match,
+            Some(json!({ "translation": translation })),
+        )
+    };
+    let (first, second) = tokio::join!(put("home"), put("building"));
+
+    let mut statuses = [first.status, second.status];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::PRECONDITION_FAILED]);
+
+    let response = app.get(&format!("{}/revisions", path), None).await;
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+
+    let etag = app.get(&path, None).await.etag();
+    let if_match = [(header::IF_MATCH, etag.as_str())];
+    let delete = || app.request(Method::DELETE, &path, None, &if_match, None);
+    let (first, second) = tokio::join!(delete(), delete());
+
+    let mut statuses = [first.status, second.status];
+    statuses.sort();
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::PRECONDITION_FAILED]);
+}
+
+#[tokio::test]
+async fn replaces_tags_of_a_word() {
+    let app = TestApp::new().await;
+    let id = app.word("hus", "house").await;
+    let path = format!("/api/words/{}/tags", id);
+
+    let response = app.put(&path, None, json!(["noun", "home"])).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    let response = app.put(&path, None, json!(["noun"])).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let response = app.get(&path, None).await;
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+    assert_eq!(response.body[0]["name"], "noun");
+
+    let response = app.get("/api/words?tag=noun", None).await;
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+
+    let response = app.get("/api/words?tag=home", None).await;
+    assert_eq!(response.body, json!([]));
+}
+
+#[tokio::test]
+async fn records_and_reverts_revisions() {
+    let app = TestApp::new().await;
+    let token = app.user("kari").await;
+    let id = app.word("hus", "house").await;
+
+    app.put(
+        &format!("/api/words/{}", id),
+        Some(&token),
+        json!({ "translation": "home" }),
+    )
+    .await;
+
+    let response = app.get(&format!("/api/words/{}/revisions", id), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let revisions = response.body.as_array().unwrap();
+    let update = revisions
+        .iter()
+        .find(|revision| revision["user_name"] == "kari")
+        .expect("no revision by kari");
+
+    let response = app
+        .post(
+            &format!("/api/words/{}/revisions/{}/revert", id, update["id"]),
+            Some(&token),
+            json!(null),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["translation"], "house");
+}
+
+#[tokio::test]
+async fn merges_duplicates() {
+    let app = TestApp::new().await;
+    let token = app.user("kari").await;
+    let kept = app.word("hus", "house").await;
+    let duplicate = app.word("hus", "house").await;
+    app.word("bil", "car").await;
+
+    let response = app.get("/api/words/duplicates", None).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+
+    let path = format!("/api/words/{}/merge", kept);
+    let merge = json!({ "duplicates": [duplicate] });
+
+    // There is no undoing it
+    let response = app.post(&path, None, merge.clone()).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(app.count("words").await, 3);
+
+    let response = app.post(&path, Some(&token), merge).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    let response = app.get(&format!("/api/words/{}", duplicate), None).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn grades_answers_and_records_reviews() {
+    let app = TestApp::new().await;
+    let token = app.user("kari").await;
+    let id = app.word("hus", "house").await;
+    let path = format!("/api/words/{}/answer", id);
+
+    let response = app
+        .post(&path, Some(&token), json!({ "answer": "Hus" }))
+        .await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(response.body["correct"], true);
+
+    let response = app
+        .post(&path, Some(&token), json!({ "answer": "bil" }))
+        .await;
+    assert_eq!(response.body["correct"], false);
+    assert_eq!(response.body["expected"], "hus");
+
+    let response = app
+        .post(
+            &path,
+            None,
+            json!({ "answer": "house", "direction": "to_source" }),
+        )
+        .await;
+    assert_eq!(response.body["correct"], true);
+
+    let response = app.get(&format!("/api/words/{}/reviews", id), None).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let reviews = response.body.as_array().unwrap();
+    assert_eq!(reviews.len(), 3);
+    assert_eq!(
+        reviews
+            .iter()
+            .filter(|review| review["user_id"].is_null())
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn answers_need_to_see_the_collection() {
+    let app = TestApp::new().await;
+    let owner = app.user("kari").await;
+    let other = app.user("ola").await;
+    let hus = app.word("hus", "house").await;
+    let id = app.collection("Mine", Some(&owner), &[hus]).await;
+    let path = format!("/api/words/{}/answer", hus);
+    let answer = json!({ "answer": "hus", "collection_id": id });
+
+    for token in [None, Some(other.as_str())] {
+        let response = app.post(&path, token, answer.clone()).await;
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+    }
+    assert_eq!(app.count("reviews").await, 0);
+
+    let response = app.post(&path, Some(&owner), answer).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    assert_eq!(app.count("reviews").await, 1);
+}
@@ -0,0 +1,391 @@
+// Generated by `drue codegen` from the API's OpenAPI schemas. Do not edit.
+
+export interface Activity {
+  action: string
+  collection_id: number
+  created_at: string
+  id: number
+  user_id?: number | null
+  user_name?: string | null
+  word?: string | null
+  word_id?: number | null
+}
+
+export interface Answer {
+  answer: string
+  collection_id?: number | null
+  direction?: Direction
+}
+
+export interface Backup {
+  path: string
+}
+
+export interface Change {
+  from: unknown
+  to: unknown
+}
+
+export interface Check {
+  /**
+   * Why the check failed, or what it found
+   */
+  detail?: string | null
+  latency_ms: number
+  name: string
+  ok: boolean
+}
+
+export interface Checkpoint {
+  /**
+   * Whether readers or writers kept the checkpoint from finishing
+   */
+  busy: boolean
+  checkpointed: number
+  /**
+   * Pages in the WAL before truncating it
+   */
+  log: number
+}
+
+export interface CloneOptions {
+  /**
+   * Keep image and audio of copied words, otherwise they are left empty
+   */
+  media?: boolean
+  /**
+   * Copy the words themselves instead of sharing them with the original
+   */
+  words?: boolean
+}
+
+export interface Collection {
+  /**
+   * Whether answers spelled in another written standard (e.g. Nynorsk in a
+   * Bokmål collection) count as correct. They are flagged either way.
+   */
+  accept_other_standards: boolean
+  cloned_from?: number | null
+  description: string
+  /**
+   * Makes this a smart collection whose words are whatever currently
+   * matches the filter, instead of the ones in `collection_words`.
+   */
+  filter?: null | WordFilter
+  id: number
+  name: string
+  /**
+   * Collections from before accounts existed, or created anonymously, have
+   * no owner. Everyone can view and edit them, only administrators manage
+   * them.
+   */
+  owner_id?: number | null
+  source_language: string
+  target_language: string
+  updated_at?: string | null
+  /**
+   * Bumped by the database on every update, including changes to the word list
+   */
+  version: number
+  /**
+   * `private`, `unlisted` (anyone with the id) or `public` (listed in discover)
+   */
+  visibility: string
+  words: Word[]
+}
+
+export interface CreateInvite {
+  /**
+   * Defaults to one week
+   */
+  expires_in_hours?: number | null
+  role: Role
+}
+
+export interface CreatePromptTemplate {
+  name: string
+  response_schema: unknown
+  system_prompt: string
+  user_prompt: string
+}
+
+export interface Credentials {
+  name: string
+  password: string
+}
+
+export type Direction = 'to_target' | 'to_source'
+
+export interface DiscoverEntry {
+  clones: number
+  description: string
+  id: number
+  name: string
+  owner?: string | null
+  source_language: string
+  target_language: string
+  word_count: number
+}
+
+/**
+ * Everything needed to move an instance, trash excluded. Ids are the ones of
+ * the exporting database and only used to connect the parts on import.
+ */
+export interface Dump {
+  collections: DumpCollection[]
+  exported_at: string
+  format_version: number
+  reviews: Review[]
+  /**
+   * Names of all tags, including the ones no word has
+   */
+  tags?: string[]
+  users: DumpUser[]
+  words: DumpWord[]
+}
+
+export interface DumpCollection {
+  accept_other_standards: boolean
+  cloned_from?: number | null
+  description: string
+  filter?: null | WordFilter
+  id: number
+  members: DumpMember[]
+  name: string
+  owner_id?: number | null
+  source_language: string
+  target_language: string
+  visibility: string
+  word_ids: number[]
+}
+
+export interface DumpMember {
+  role: Role
+  user_id: number
+}
+
+export interface DumpUser {
+  id: number
+  name: string
+  password_hash: string
+}
+
+export type DumpWord = Word & {
+  spellings: Spelling[]
+  tags: string[]
+}
+
+/**
+ * Words that are the same after normalization. `exact` is set when they
+ * also match character for character.
+ */
+export interface DuplicateGroup {
+  exact: boolean
+  translation: string
+  word: string
+  words: Word[]
+}
+
+/**
+ * Body of every error response.
+ */
+export interface ErrorBody {
+  details: unknown
+  /**
+   * Stable, machine readable code such as `not_found`
+   */
+  error: string
+  message: string
+}
+
+export interface Grade {
+  correct: boolean
+  expected: string
+  /**
+   * Set to the standard's language code when the answer was spelled in
+   * another written standard than the one being taught.
+   */
+  other_standard?: string | null
+}
+
+export interface Health {
+  ok: boolean
+}
+
+export type ImportMode = 'merge' | 'restore'
+
+export interface ImportSummary {
+  collections: number
+  reviews: number
+  users: number
+  words: number
+}
+
+export interface Integrity {
+  ok: boolean
+  problems: string[]
+}
+
+export interface Invite {
+  collection_id: number
+  expires_at: string
+  id: number
+  role: Role
+  token: string
+}
+
+export interface LlmDailyStats {
+  avg_latency_ms: number
+  calls: number
+  completion_tokens: number
+  cost: number
+  day: string
+  failures: number
+  model: string
+  prompt_tokens: number
+}
+
+export interface LlmStats {
+  daily_token_budget?: number | null
+  days: LlmDailyStats[]
+  tokens_today: number
+  total_cost: number
+  total_tokens: number
+}
+
+export interface Member {
+  created_at: string
+  name: string
+  role: Role
+  user_id: number
+}
+
+export interface Merge {
+  duplicates: number[]
+}
+
+export interface PromptTemplate {
+  id: number
+  name: string
+  response_schema: Record<string, unknown>
+  system_prompt: string
+  user_prompt: string
+  version: number
+}
+
+export interface Question {
+  options: QuestionOption[]
+  question: string
+}
+
+export interface QuestionOption {
+  correct: boolean
+  name: string
+}
+
+export interface Readiness {
+  checks: Check[]
+  ready: boolean
+}
+
+export interface RenderedPrompt {
+  name: string
+  schema: unknown
+  system: string
+  template_id: number
+  user: string
+  version: number
+}
+
+export interface Review {
+  box: number
+  correct: boolean
+  created_at: string
+  due_at: string
+  id: number
+  user_id?: number | null
+  word_id: number
+}
+
+export interface Revision {
+  changes: Record<string, Change>
+  created_at: string
+  id: number
+  user_id?: number | null
+  user_name?: string | null
+  word_id: number
+}
+
+/**
+ * Roles are ordered, each one allows everything the previous one does.
+ */
+export type Role = 'viewer' | 'editor' | 'owner'
+
+export interface Session {
+  token: string
+}
+
+export interface SetRole {
+  role: Role
+}
+
+/**
+ * How a word is written in one written standard of its language, e.g. the
+ * Nynorsk (`nn`) form of a Bokmål (`nb`) word.
+ */
+export interface Spelling {
+  language: string
+  spelling: string
+}
+
+export interface Tag {
+  id: number
+  name: string
+}
+
+export interface TrashItem {
+  deleted_at: string
+  id: number
+  kind: string
+  name: string
+}
+
+export type TrashKind = 'words' | 'collections' | 'tags'
+
+export interface User {
+  id: number
+  name: string
+}
+
+export interface Word {
+  audio: string
+  difficulty?: number | null
+  id: number
+  image: string
+  part_of_speech?: string | null
+  source_language?: string | null
+  target_language?: string | null
+  translation: string
+  updated_at?: string | null
+  /**
+   * Bumped by the database on every update
+   */
+  version: number
+  word: string
+}
+
+/**
+ * Saved filter of a smart collection, also used for `/api/words?tag=`. All
+ * given conditions have to match.
+ */
+export interface WordFilter {
+  /**
+   * Only words that are (or aren't) due for review. Words never reviewed
+   * count as due.
+   */
+  due?: boolean | null
+  max_difficulty?: number | null
+  min_difficulty?: number | null
+  part_of_speech?: string | null
+  tags?: string[]
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Body of an error response, see `drue::endpoints::error::ErrorBody`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ApiError {
+    #[serde(skip, default = "internal_server_error")]
+    pub status: StatusCode,
+    pub error: String,
+    pub message: String,
+    #[serde(default)]
+    pub details: Value,
+}
+
+fn internal_server_error() -> StatusCode {
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+impl ApiError {
+    /// Reads the error out of a response body. Older routes fail without
+    /// one, in which case the body text becomes the message.
+    pub(crate) fn parse(status: StatusCode, body: &str) -> Self {
+        match serde_json::from_str::<ApiError>(body) {
+            Ok(error) => ApiError { status, ..error },
+            Err(_) => ApiError {
+                status,
+                error: "unknown".to_string(),
+                message: body.to_string(),
+                details: Value::Null,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The server could not be reached or answered with something unreadable
+    Request(reqwest::Error),
+    Unauthorized(ApiError),
+    Forbidden(ApiError),
+    NotFound(ApiError),
+    /// `If-Match` did not match, someone else changed the resource
+    PreconditionFailed(ApiError),
+    Api(ApiError),
+}
+
+impl Error {
+    pub(crate) fn from_response(status: StatusCode, body: &str) -> Self {
+        let error = ApiError::parse(status, body);
+
+        match status {
+            StatusCode::UNAUTHORIZED => Error::Unauthorized(error),
+            StatusCode::FORBIDDEN => Error::Forbidden(error),
+            StatusCode::NOT_FOUND => Error::NotFound(error),
+            StatusCode::PRECONDITION_FAILED => Error::PreconditionFailed(error),
+            _ => Error::Api(error),
+        }
+    }
+
+    /// The error the server responded with, if it responded at all.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Error::Request(_) => None,
+            Error::Unauthorized(error)
+            | Error::Forbidden(error)
+            | Error::NotFound(error)
+            | Error::PreconditionFailed(error)
+            | Error::Api(error) => Some(error),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Request(err) => write!(f, "request failed: {}", err),
+            _ => {
+                let error = self
+                    .api_error()
+                    .expect("only requests fail without a response");
+
+                write!(f, "{} ({}): {}", error.status, error.error, error.message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Request(err)
+    }
+}
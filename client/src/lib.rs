@@ -0,0 +1,364 @@
+//! Typed async client for the Drue API, using the server's own model types.
+
+use reqwest::{header, Method, RequestBuilder, Response};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+pub use drue::{
+    controllers::{
+        collections::{CloneOptions, Collection, DiscoverEntry, DiscoverParams},
+        questions::{Option as QuestionOption, Question},
+        reviews::Review,
+        revisions::Revision,
+        tags::Tag,
+        users::User,
+        words::{DuplicateGroup, Spelling, Word},
+    },
+    endpoints::{users::Session, words::Answer},
+    languages::grading::{Direction, Grade},
+};
+
+pub use self::{
+    error::{ApiError, Error},
+    pages::Pages,
+};
+
+mod error;
+mod pages;
+
+/// A resource together with the `ETag` to pass as `if_match` when changing it.
+#[derive(Clone, Debug)]
+pub struct Versioned<T> {
+    pub value: T,
+    pub etag: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl Client {
+    /// `base_url` is where the server listens, e.g. `http://localhost:3000`.
+    pub fn new(base_url: &str) -> Self {
+        Client {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    /// Sends `token` as bearer token with every request.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    /// Logs in and uses the new session for the following requests.
+    pub async fn login(&mut self, name: &str, password: &str) -> Result<User, Error> {
+        let session: Session = self
+            .send(
+                self.request(Method::POST, "/api/sessions")
+                    .json(&json!({ "name": name, "password": password })),
+            )
+            .await?;
+
+        self.token = Some(session.token);
+
+        self.send(self.request(Method::GET, "/api/users/me")).await
+    }
+
+    pub async fn words(&self) -> Result<Vec<Word>, Error> {
+        self.send(self.request(Method::GET, "/api/words")).await
+    }
+
+    pub async fn words_tagged(&self, tag: &str) -> Result<Vec<Word>, Error> {
+        self.get_query("/api/words", &[("tag".to_string(), tag.to_string())])
+            .await
+    }
+
+    /// Pages through the words, optionally only those with `tag`.
+    pub fn word_pages(&self, tag: Option<&str>, page_size: i64) -> Pages<'_, Word> {
+        let query = tag
+            .map(|tag| vec![("tag".to_string(), tag.to_string())])
+            .unwrap_or_default();
+
+        Pages::new(self, "/api/words", query, page_size)
+    }
+
+    pub async fn word(&self, id: i64) -> Result<Versioned<Word>, Error> {
+        self.send_versioned(self.request(Method::GET, &format!("/api/words/{}", id)))
+            .await
+    }
+
+    /// `word` holds the columns to set, e.g. `{"word": "hus", "translation": "house"}`.
+    pub async fn create_word(&self, word: &Value) -> Result<Word, Error> {
+        self.send(self.request(Method::POST, "/api/words").json(word))
+            .await
+    }
+
+    /// Changes the given columns. With `if_match` the update fails with
+    /// `Error::PreconditionFailed` if the word changed since that `ETag`.
+    pub async fn update_word(
+        &self,
+        id: i64,
+        changes: &Value,
+        if_match: Option<&str>,
+    ) -> Result<Versioned<Word>, Error> {
+        let request = self
+            .request(Method::PUT, &format!("/api/words/{}", id))
+            .json(changes);
+
+        self.send_versioned(with_if_match(request, if_match)).await
+    }
+
+    /// Moves the word to the trash.
+    pub async fn delete_word(&self, id: i64, if_match: Option<&str>) -> Result<(), Error> {
+        let request = self.request(Method::DELETE, &format!("/api/words/{}", id));
+
+        self.send(with_if_match(request, if_match)).await
+    }
+
+    pub async fn spellings(&self, id: i64) -> Result<Vec<Spelling>, Error> {
+        self.send(self.request(Method::GET, &format!("/api/words/{}/spellings", id)))
+            .await
+    }
+
+    pub async fn set_spellings(
+        &self,
+        id: i64,
+        spellings: &[Spelling],
+    ) -> Result<Vec<Spelling>, Error> {
+        self.send(
+            self.request(Method::PUT, &format!("/api/words/{}/spellings", id))
+                .json(spellings),
+        )
+        .await
+    }
+
+    pub async fn word_tags(&self, id: i64) -> Result<Vec<Tag>, Error> {
+        self.send(self.request(Method::GET, &format!("/api/words/{}/tags", id)))
+            .await
+    }
+
+    /// Replaces the tags of a word, creating tags that don't exist yet.
+    pub async fn set_word_tags(&self, id: i64, names: &[String]) -> Result<Vec<Tag>, Error> {
+        self.send(
+            self.request(Method::PUT, &format!("/api/words/{}/tags", id))
+                .json(names),
+        )
+        .await
+    }
+
+    pub async fn word_reviews(&self, id: i64) -> Result<Vec<Review>, Error> {
+        self.send(self.request(Method::GET, &format!("/api/words/{}/reviews", id)))
+            .await
+    }
+
+    pub async fn word_revisions(&self, id: i64) -> Result<Vec<Revision>, Error> {
+        self.send(self.request(Method::GET, &format!("/api/words/{}/revisions", id)))
+            .await
+    }
+
+    pub async fn revert_word(
+        &self,
+        id: i64,
+        revision_id: i64,
+        if_match: Option<&str>,
+    ) -> Result<Versioned<Word>, Error> {
+        let request = self.request(
+            Method::POST,
+            &format!("/api/words/{}/revisions/{}/revert", id, revision_id),
+        );
+
+        self.send_versioned(with_if_match(request, if_match)).await
+    }
+
+    pub async fn duplicate_words(&self) -> Result<Vec<DuplicateGroup>, Error> {
+        self.send(self.request(Method::GET, "/api/words/duplicates"))
+            .await
+    }
+
+    /// Merges `duplicates` into the word `id`, returning the surviving word.
+    /// Needs a token.
+    pub async fn merge_words(&self, id: i64, duplicates: &[i64]) -> Result<Word, Error> {
+        self.send(
+            self.request(Method::POST, &format!("/api/words/{}/merge", id))
+                .json(&json!({ "duplicates": duplicates })),
+        )
+        .await
+    }
+
+    /// Grades an answer and, when logged in, records the review.
+    pub async fn answer(&self, id: i64, answer: &Answer) -> Result<Grade, Error> {
+        self.send(
+            self.request(Method::POST, &format!("/api/words/{}/answer", id))
+                .json(answer),
+        )
+        .await
+    }
+
+    /// A multiple choice question about a word of the collection.
+    pub async fn question(&self, collection_id: i64) -> Result<Question, Error> {
+        self.send(self.request(Method::GET, &format!("/api/question/{}", collection_id)))
+            .await
+    }
+
+    pub async fn collections(&self) -> Result<Vec<Collection>, Error> {
+        self.send(self.request(Method::GET, "/api/collections"))
+            .await
+    }
+
+    pub async fn collection(&self, id: i64) -> Result<Versioned<Collection>, Error> {
+        self.send_versioned(self.request(Method::GET, &format!("/api/collections/{}", id)))
+            .await
+    }
+
+    /// `collection` holds the columns to set, plus `words` as a list of ids.
+    pub async fn create_collection(&self, collection: &Value) -> Result<Collection, Error> {
+        self.send(
+            self.request(Method::POST, "/api/collections")
+                .json(collection),
+        )
+        .await
+    }
+
+    pub async fn update_collection(
+        &self,
+        id: i64,
+        changes: &Value,
+        if_match: Option<&str>,
+    ) -> Result<Versioned<Collection>, Error> {
+        let request = self
+            .request(Method::PUT, &format!("/api/collections/{}", id))
+            .json(changes);
+
+        self.send_versioned(with_if_match(request, if_match)).await
+    }
+
+    /// Moves the collection to the trash.
+    pub async fn delete_collection(&self, id: i64, if_match: Option<&str>) -> Result<(), Error> {
+        let request = self.request(Method::DELETE, &format!("/api/collections/{}", id));
+
+        self.send(with_if_match(request, if_match)).await
+    }
+
+    pub async fn discover(&self, params: &DiscoverParams) -> Result<Vec<DiscoverEntry>, Error> {
+        self.get_query("/api/discover", &query_pairs(params)).await
+    }
+
+    /// Pages through public collections. `limit` and `offset` of `params`
+    /// are replaced by the pages.
+    pub fn discover_pages(
+        &self,
+        params: &DiscoverParams,
+        page_size: i64,
+    ) -> Pages<'_, DiscoverEntry> {
+        let query = query_pairs(&DiscoverParams {
+            limit: None,
+            offset: None,
+            ..params.clone()
+        });
+
+        Pages::new(self, "/api/discover", query, page_size)
+    }
+
+    /// Copies a public or shared collection into the logged in account.
+    pub async fn clone_collection(
+        &self,
+        id: i64,
+        options: &CloneOptions,
+    ) -> Result<Collection, Error> {
+        self.send(
+            self.request(Method::POST, &format!("/api/collections/{}/clone", id))
+                .json(options),
+        )
+        .await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path));
+
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    pub(crate) async fn get_query<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(String, String)],
+    ) -> Result<T, Error> {
+        self.send(self.request(Method::GET, path).query(query))
+            .await
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, Error> {
+        let response = checked(request.send().await?).await?;
+
+        Ok(response.json().await?)
+    }
+
+    async fn send_versioned<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<Versioned<T>, Error> {
+        let response = checked(request.send().await?).await?;
+
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(Versioned {
+            value: response.json().await?,
+            etag,
+        })
+    }
+}
+
+async fn checked(response: Response) -> Result<Response, Error> {
+    let status = response.status();
+
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await?;
+
+    Err(Error::from_response(status, &body))
+}
+
+fn with_if_match(request: RequestBuilder, if_match: Option<&str>) -> RequestBuilder {
+    match if_match {
+        Some(etag) => request.header(header::IF_MATCH, etag),
+        None => request,
+    }
+}
+
+/// Query string pairs of the set fields of `params`, a flat struct.
+fn query_pairs(params: &impl Serialize) -> Vec<(String, String)> {
+    let value = serde_json::to_value(params).unwrap_or_default();
+
+    value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| match value {
+            Value::Null => None,
+            Value::String(text) => Some((key.clone(), text.clone())),
+            other => Some((key.clone(), other.to_string())),
+        })
+        .collect()
+}
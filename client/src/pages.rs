@@ -0,0 +1,68 @@
+use serde::de::DeserializeOwned;
+
+use crate::{Client, Error};
+
+/// Walks a listing that takes `limit` and `offset`, one page per request.
+/// The server may return fewer rows than asked for, so only an empty page
+/// ends the listing.
+pub struct Pages<'a, T> {
+    client: &'a Client,
+    path: String,
+    query: Vec<(String, String)>,
+    limit: i64,
+    offset: i64,
+    done: bool,
+    _item: std::marker::PhantomData<T>,
+}
+
+impl<'a, T: DeserializeOwned> Pages<'a, T> {
+    pub(crate) fn new(
+        client: &'a Client,
+        path: &str,
+        query: Vec<(String, String)>,
+        limit: i64,
+    ) -> Self {
+        Pages {
+            client,
+            path: path.to_string(),
+            query,
+            limit: limit.max(1),
+            offset: 0,
+            done: false,
+            _item: std::marker::PhantomData,
+        }
+    }
+
+    /// The next page, or `None` once the listing is exhausted.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<T>>, Error> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let mut query = self.query.clone();
+        query.push(("limit".to_string(), self.limit.to_string()));
+        query.push(("offset".to_string(), self.offset.to_string()));
+
+        let page: Vec<T> = self.client.get_query(&self.path, &query).await?;
+
+        self.done = page.is_empty();
+        self.offset += page.len() as i64;
+
+        if page.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(page))
+        }
+    }
+
+    /// Fetches the remaining pages into one list.
+    pub async fn collect_all(mut self) -> Result<Vec<T>, Error> {
+        let mut items = Vec::new();
+
+        while let Some(page) = self.next_page().await? {
+            items.extend(page);
+        }
+
+        Ok(items)
+    }
+}
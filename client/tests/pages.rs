@@ -0,0 +1,83 @@
+//! Runs the client against the router on a local port, backed by a fresh
+//! in-memory database.
+
+use std::{str::FromStr, sync::Arc};
+
+use drue::{metrics::Metrics, routes::create_router, AppState, MIGRATOR};
+use drue_client::Client;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
+use tokio::net::TcpListener;
+
+/// More words than the server returns in one page.
+const WORDS: usize = 1200;
+
+/// Serves a database holding [`WORDS`] words and returns its URL.
+async fn serve() -> String {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:")
+        .unwrap()
+        .foreign_keys(true);
+    let pool: SqlitePool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .unwrap();
+
+    MIGRATOR.run(&pool).await.expect("migrations failed");
+
+    sqlx::query(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?) INSERT INTO words (word, translation, image, audio) SELECT 'word ' || i, 'translation ' || i, '', '' FROM n",
+    )
+    .bind(WORDS as i64)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let state = Arc::new(AppState {
+        pool,
+        http_client: reqwest::Client::new(),
+        llm_url: "http://127.0.0.1:1/v1/chat/completions".to_string(),
+        question_pool_size: 0,
+        question_retries: 1,
+        learner_level: "beginner".to_string(),
+        daily_token_budget: None,
+        user_daily_token_budget: None,
+        prompt_token_price: 2.5,
+        completion_token_price: 10.0,
+        trash_retention_days: 30,
+        admin_users: vec![],
+        backup: None,
+        metrics: Metrics::new(),
+        pregenerating: Default::default(),
+    });
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        axum::serve(listener, create_router(state)).await.unwrap();
+    });
+
+    format!("http://{}", address)
+}
+
+#[tokio::test]
+async fn pages_past_the_server_page_size() {
+    let client = Client::new(&serve().await);
+
+    // Above, at and below what the server returns at once
+    for page_size in [1000, 500, 300] {
+        let words = client
+            .word_pages(None, page_size)
+            .collect_all()
+            .await
+            .unwrap();
+
+        assert_eq!(words.len(), WORDS, "page size {}", page_size);
+        assert!(words.windows(2).all(|pair| pair[0].id < pair[1].id));
+    }
+}